@@ -1,8 +1,9 @@
 use dioxus::prelude::*;
-use qrcode_lib::fancy::FancyQr;
+use qrcode_lib::fancy::{FancyQr, FancyOptions, OverlayScale, sanitize_svg_logo};
+use qrcode_lib::render::svg_data_uri;
 use gloo_timers::future::sleep;
 use std::time::Duration;
-use crate::types::{QrStyle, get_custom_style_options};
+use crate::types::QrStyle;
 use super::{Header, UrlInput, StyleSelector, PreviewPanel, Footer, LogoUploader, ColorSchemePicker};
 
 const LOGO_SVG: &str = include_str!("../../assets/logo-icon.svg");
@@ -20,34 +21,43 @@ pub fn Home() -> Element {
     let data_color = use_signal(|| "#4d3695".to_string());
     let finder_color = use_signal(|| "#4d3695".to_string());
 
-    // Generate QR code when inputs change
+    // Encode only when the content changes
+    let mut encoded = use_signal(|| Option::<FancyQr>::None);
     use_effect(move || {
         let url = content();
+        if url.is_empty() {
+            return;
+        }
+        if let Ok(qr) = FancyQr::from_text(&url) {
+            encoded.set(Some(qr));
+        }
+    });
+
+    // Re-render the encoded code when it or the styling changes
+    use_effect(move || {
         let current_style = style();
         let logo = custom_logo();
         let bg = background_color();
         let data = data_color();
         let finder = finder_color();
 
-        if url.is_empty() {
+        let Some(qr) = encoded() else {
             return;
-        }
-
-        let qr = match FancyQr::from_text(&url) {
-            Ok(q) => q,
-            Err(_) => return,
         };
 
         // Use custom logo if provided, otherwise use default
         let logo_svg = logo.as_deref().unwrap_or(LOGO_SVG);
         let logo_base64 = if !logo_svg.is_empty() {
-            base64_encode_svg(logo_svg)
+            svg_data_uri(&sanitize_svg_logo(logo_svg))
         } else {
             String::new()
         };
 
         // Use custom colors if provided
-        let options = get_custom_style_options(current_style, &logo_base64, &bg, &data, &finder);
+        let mut options = FancyOptions::from_theme(current_style.theme()).with_colors(&bg, &data, &finder);
+        if !logo_base64.is_empty() && options.overlay_scale != OverlayScale::NONE {
+            options.center_image_url = Some(logo_base64);
+        }
         let svg = qr.render_svg(&options);
         svg_output.set(svg);
     });
@@ -163,31 +173,3 @@ async fn copy_to_clipboard(content: String) -> bool {
     }
     false
 }
-
-// Simple base64 encoding for SVG data URI
-fn base64_encode_svg(svg: &str) -> String {
-    let mut encoded = String::new();
-    let bytes = svg.as_bytes();
-    
-    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    
-    for chunk in bytes.chunks(3) {
-        let mut buf = [0u8; 3];
-        for (i, &byte) in chunk.iter().enumerate() {
-            buf[i] = byte;
-        }
-        
-        let b1 = (buf[0] >> 2) as usize;
-        let b2 = (((buf[0] & 0x03) << 4) | (buf[1] >> 4)) as usize;
-        let b3 = (((buf[1] & 0x0f) << 2) | (buf[2] >> 6)) as usize;
-        let b4 = (buf[2] & 0x3f) as usize;
-        
-        encoded.push(ALPHABET[b1] as char);
-        encoded.push(ALPHABET[b2] as char);
-        encoded.push(if chunk.len() > 1 { ALPHABET[b3] as char } else { '=' });
-        encoded.push(if chunk.len() > 2 { ALPHABET[b4] as char } else { '=' });
-    }
-    
-    format!("data:image/svg+xml;base64,{}", encoded)
-}
-
@@ -0,0 +1,16 @@
+// Benchmark: Encoding long payloads
+//
+// Measures segment classification and full encoding of a 4000-digit string.
+// Run with `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use qrcode_lib::{QrCode, QrCodeEcc, QrSegment};
+
+fn numeric(c: &mut Criterion) {
+    let digits = "0123456789".repeat(400);
+    c.bench_function("make_segments 4000 digits", |b| b.iter(|| QrSegment::make_segments(black_box(&digits))));
+    c.bench_function("encode_text 4000 digits", |b| b.iter(|| QrCode::encode_text(black_box(&digits), QrCodeEcc::Low)));
+}
+
+criterion_group!(benches, numeric);
+criterion_main!(benches);
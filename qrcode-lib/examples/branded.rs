@@ -3,7 +3,7 @@
 // This example demonstrates creating a branded QR code with a logo
 // in the center, using custom brand colors.
 
-use qrcode_lib::fancy::{FancyQr, FancyOptions, ModuleShape, FinderShape};
+use qrcode_lib::fancy::{FancyQr, FancyOptions, ModuleShape, FinderShape, OverlayScale};
 use std::fs::{self, File};
 use std::io::Write;
 
@@ -18,21 +18,22 @@ fn main() {
     // Style 1: With embedded logo (using data URI)
     println!("1. Generating QR with embedded logo...");
     let qr = FancyQr::from_text(url).expect("Failed to create QR code");
-    let mut options = FancyOptions::default();
-    
-    // Brand colors
-    options.color_background = "#FFFFFF".to_string();
-    options.color_data = "#4d3695".to_string();        // Brand purple
-    options.color_finder = "#4d3695".to_string();       // Brand purple
-    
-    // Rounded, modern look
-    options.shape_module = ModuleShape::RoundedSquare(0.3);
-    options.shape_finder = FinderShape::Rounded(1.5);
-    
-    // Embed the logo using a file path reference
-    // Note: In production, you'd want to convert this to a data URI or use an absolute URL
-    options.center_image_url = Some("logo-icon.svg".to_string());
-    options.overlay_scale = 0.3; // 30% size for logo visibility
+    let options = FancyOptions {
+        // Brand colors
+        color_background: "#FFFFFF".to_string(),
+        color_data: "#4d3695".to_string(),        // Brand purple
+        color_finder: "#4d3695".to_string(),       // Brand purple
+        
+        // Rounded, modern look
+        shape_module: ModuleShape::RoundedSquare(0.3),
+        shape_finder: FinderShape::Rounded(1.5),
+        
+        // Embed the logo using a file path reference
+        // Note: In production, you'd want to convert this to a data URI or use an absolute URL
+        center_image_url: Some("logo-icon.svg".to_string()),
+        overlay_scale: OverlayScale::new(0.3).unwrap(), // 30% size for logo visibility
+        ..FancyOptions::default()
+    };
     
     let svg = qr.render_svg(&options);
     save_svg("output/with_logo.svg", &svg);
@@ -40,21 +41,22 @@ fn main() {
     // Style 2: With base64 embedded logo
     println!("2. Generating QR with base64 embedded logo...");
     let qr = FancyQr::from_text(url).expect("Failed to create QR code");
-    let mut options = FancyOptions::default();
-    
-    options.color_background = "#F8F7FF".to_string();  // Very light purple
-    options.color_data = "#4d3695".to_string();
-    options.color_finder = "#6B4B8A".to_string();
-    
-    options.shape_module = ModuleShape::Circle;
-    options.shape_finder = FinderShape::Rounded(2.0);
+    let mut options = FancyOptions {
+        color_background: "#F8F7FF".to_string(),  // Very light purple
+        color_data: "#4d3695".to_string(),
+        color_finder: "#6B4B8A".to_string(),
+        
+        shape_module: ModuleShape::Circle,
+        shape_finder: FinderShape::Rounded(2.0),
+        ..FancyOptions::default()
+    };
     
     // Read and embed logo as base64 data URI
     let logo_svg = fs::read_to_string("logo-icon.svg")
         .expect("Failed to read logo file");
     let logo_base64 = base64_encode_svg(&logo_svg);
     options.center_image_url = Some(logo_base64);
-    options.overlay_scale = 0.28;
+    options.overlay_scale = OverlayScale::new(0.28).unwrap();
     
     let svg = qr.render_svg(&options);
     save_svg("output/with_logo_base64.svg", &svg);
@@ -62,21 +64,22 @@ fn main() {
     // Style 3: Minimal style with logo
     println!("3. Generating minimal style with logo...");
     let qr = FancyQr::from_text(url).expect("Failed to create QR code");
-    let mut options = FancyOptions::default();
-    
-    options.color_background = "#FFFFFF".to_string();
-    options.color_data = "#000000".to_string();
-    options.color_finder = "#4d3695".to_string();      // Purple finders for brand recognition
-    
-    options.shape_module = ModuleShape::Square;
-    options.shape_finder = FinderShape::Rounded(1.0);
+    let mut options = FancyOptions {
+        color_background: "#FFFFFF".to_string(),
+        color_data: "#000000".to_string(),
+        color_finder: "#4d3695".to_string(),      // Purple finders for brand recognition
+        
+        shape_module: ModuleShape::Square,
+        shape_finder: FinderShape::Rounded(1.0),
+        ..FancyOptions::default()
+    };
     
     // Read logo
     let logo_svg = fs::read_to_string("logo-icon.svg")
         .expect("Failed to read logo file");
     let logo_base64 = base64_encode_svg(&logo_svg);
     options.center_image_url = Some(logo_base64);
-    options.overlay_scale = 0.25;
+    options.overlay_scale = OverlayScale::new(0.25).unwrap();
     
     let svg = qr.render_svg(&options);
     save_svg("output/minimal_logo.svg", &svg);
@@ -84,22 +87,23 @@ fn main() {
     // Style 4: Gradient-inspired with logo (Premium look)
     println!("4. Generating gradient-inspired style with logo...");
     let qr = FancyQr::from_text(url).expect("Failed to create QR code");
-    let mut options = FancyOptions::default();
-    
-    // Gradient-inspired colors
-    options.color_background = "#F5F3FF".to_string();  // Very light purple (cleaner)
-    options.color_data = "#4d3695".to_string();        // Brand purple
-    options.color_finder = "#5B34A8".to_string();      // Slightly lighter purple for contrast
-    
-    // Smooth, modern shapes
-    options.shape_module = ModuleShape::Circle;        // Circular dots for premium look
-    options.shape_finder = FinderShape::Rounded(2.0);  // Nicely rounded finders
+    let mut options = FancyOptions {
+        // Gradient-inspired colors
+        color_background: "#F5F3FF".to_string(),  // Very light purple (cleaner)
+        color_data: "#4d3695".to_string(),        // Brand purple
+        color_finder: "#5B34A8".to_string(),      // Slightly lighter purple for contrast
+        
+        // Smooth, modern shapes
+        shape_module: ModuleShape::Circle,        // Circular dots for premium look
+        shape_finder: FinderShape::Rounded(2.0),  // Nicely rounded finders
+        ..FancyOptions::default()
+    };
     
     let logo_svg = fs::read_to_string("logo-icon.svg")
         .expect("Failed to read logo file");
     let logo_base64 = base64_encode_svg(&logo_svg);
     options.center_image_url = Some(logo_base64);
-    options.overlay_scale = 0.28;  // Slightly smaller for better scannability
+    options.overlay_scale = OverlayScale::new(0.28).unwrap();  // Slightly smaller for better scannability
     
     let svg = qr.render_svg(&options);
     save_svg("output/gradient_logo.svg", &svg);
@@ -107,22 +111,23 @@ fn main() {
     // Style 5: Ultra Premium (white background, bold purple)
     println!("5. Generating ultra-premium style with logo...");
     let qr = FancyQr::from_text(url).expect("Failed to create QR code");
-    let mut options = FancyOptions::default();
-    
-    // Clean, professional design
-    options.color_background = "#FFFFFF".to_string();  // Pure white
-    options.color_data = "#4d3695".to_string();        // Brand purple
-    options.color_finder = "#4d3695".to_string();      // Brand purple
-    
-    // Premium rounded squares
-    options.shape_module = ModuleShape::RoundedSquare(0.35);
-    options.shape_finder = FinderShape::Rounded(1.8);
+    let mut options = FancyOptions {
+        // Clean, professional design
+        color_background: "#FFFFFF".to_string(),  // Pure white
+        color_data: "#4d3695".to_string(),        // Brand purple
+        color_finder: "#4d3695".to_string(),      // Brand purple
+        
+        // Premium rounded squares
+        shape_module: ModuleShape::RoundedSquare(0.35),
+        shape_finder: FinderShape::Rounded(1.8),
+        ..FancyOptions::default()
+    };
     
     let logo_svg = fs::read_to_string("logo-icon.svg")
         .expect("Failed to read logo file");
     let logo_base64 = base64_encode_svg(&logo_svg);
     options.center_image_url = Some(logo_base64);
-    options.overlay_scale = 0.26;
+    options.overlay_scale = OverlayScale::new(0.26).unwrap();
     
     let svg = qr.render_svg(&options);
     save_svg("output/premium.svg", &svg);
@@ -130,19 +135,20 @@ fn main() {
     // Style 6: Branded Finders (No center logo - finders ARE the branding!)
     println!("6. Generating QR with branded finder patterns...");
     let qr = FancyQr::from_text(url).expect("Failed to create QR code");
-    let mut options = FancyOptions::default();
-    
-    // Clean design focusing on the branded finder patterns
-    options.color_background = "#FFFFFF".to_string();
-    options.color_data = "#1a1a1a".to_string();         // Dark gray for subtle data
-    options.color_finder = "#4d3695".to_string();       // Brand purple for prominent finders
-    
-    // Smooth, modern shapes
-    options.shape_module = ModuleShape::RoundedSquare(0.25);
-    options.shape_finder = FinderShape::Rounded(2.2);   // Heavily rounded
-    
-    // NO center overlay - the branded finders are the focal point!
-    options.overlay_scale = 0.0;
+    let options = FancyOptions {
+        // Clean design focusing on the branded finder patterns
+        color_background: "#FFFFFF".to_string(),
+        color_data: "#1a1a1a".to_string(),         // Dark gray for subtle data
+        color_finder: "#4d3695".to_string(),       // Brand purple for prominent finders
+        
+        // Smooth, modern shapes
+        shape_module: ModuleShape::RoundedSquare(0.25),
+        shape_finder: FinderShape::Rounded(2.2),   // Heavily rounded
+        
+        // NO center overlay - the branded finders are the focal point!
+        overlay_scale: OverlayScale::NONE,
+        ..FancyOptions::default()
+    };
     
     let svg = qr.render_svg(&options);
     save_svg("output/branded_finders.svg", &svg);
@@ -150,16 +156,17 @@ fn main() {
     // Style 7: Ultra Minimal with Branded Finders
     println!("7. Generating ultra-minimal with branded finders...");
     let qr = FancyQr::from_text(url).expect("Failed to create QR code");
-    let mut options = FancyOptions::default();
-    
-    // Maximum contrast, maximum brand visibility
-    options.color_background = "#FFFFFF".to_string();   // Pure white
-    options.color_data = "#000000".to_string();         // Pure black
-    options.color_finder = "#4d3695".to_string();       // Brand purple stands out
-    
-    // Clean, professional
-    options.shape_module = ModuleShape::Square;
-    options.shape_finder = FinderShape::Rounded(1.5);
+    let options = FancyOptions {
+        // Maximum contrast, maximum brand visibility
+        color_background: "#FFFFFF".to_string(),   // Pure white
+        color_data: "#000000".to_string(),         // Pure black
+        color_finder: "#4d3695".to_string(),       // Brand purple stands out
+        
+        // Clean, professional
+        shape_module: ModuleShape::Square,
+        shape_finder: FinderShape::Rounded(1.5),
+        ..FancyOptions::default()
+    };
     
     let svg = qr.render_svg(&options);
     save_svg("output/finders_minimal.svg", &svg);
@@ -167,16 +174,17 @@ fn main() {
     // Style 8: Gradient-inspired with Branded Finders
     println!("8. Generating gradient style with branded finders...");
     let qr = FancyQr::from_text(url).expect("Failed to create QR code");
-    let mut options = FancyOptions::default();
-    
-    // Gradient look with prominent purple finders
-    options.color_background = "#FAF5FF".to_string();   // Light purple
-    options.color_data = "#6B4B8A".to_string();         // Medium purple
-    options.color_finder = "#4d3695".to_string();       // Brand purple (darkest)
-    
-    // All rounded for cohesive premium look
-    options.shape_module = ModuleShape::Circle;
-    options.shape_finder = FinderShape::Rounded(2.5);
+    let options = FancyOptions {
+        // Gradient look with prominent purple finders
+        color_background: "#FAF5FF".to_string(),   // Light purple
+        color_data: "#6B4B8A".to_string(),         // Medium purple
+        color_finder: "#4d3695".to_string(),       // Brand purple (darkest)
+        
+        // All rounded for cohesive premium look
+        shape_module: ModuleShape::Circle,
+        shape_finder: FinderShape::Rounded(2.5),
+        ..FancyOptions::default()
+    };
     
     let svg = qr.render_svg(&options);
     save_svg("output/gradient_finders.svg", &svg);
@@ -184,23 +192,24 @@ fn main() {
     // Style 9: Gradient Colors with Rectangular Modules (Gradient Minimal with Logo)
     println!("9. Generating gradient minimal with logo...");
     let qr = FancyQr::from_text(url).expect("Failed to create QR code");
-    let mut options = FancyOptions::default();
-    
-    // Gradient color palette with rectangular modules
-    options.color_background = "#FAF5FF".to_string();   // Light purple background
-    options.color_data = "#6B4B8A".to_string();         // Medium purple data
-    options.color_finder = "#4d3695".to_string();       // Brand purple finders
-    
-    // Rectangular modules for clean, scannable look
-    options.shape_module = ModuleShape::Square;
-    options.shape_finder = FinderShape::Rounded(1.5);   // Rounded finders for brand
+    let mut options = FancyOptions {
+        // Gradient color palette with rectangular modules
+        color_background: "#FAF5FF".to_string(),   // Light purple background
+        color_data: "#6B4B8A".to_string(),         // Medium purple data
+        color_finder: "#4d3695".to_string(),       // Brand purple finders
+        
+        // Rectangular modules for clean, scannable look
+        shape_module: ModuleShape::Square,
+        shape_finder: FinderShape::Rounded(1.5),   // Rounded finders for brand
+        ..FancyOptions::default()
+    };
     
     // Add logo to center
     let logo_svg = fs::read_to_string("logo-icon.svg")
         .expect("Failed to read logo file");
     let logo_base64 = base64_encode_svg(&logo_svg);
     options.center_image_url = Some(logo_base64);
-    options.overlay_scale = 0.25;
+    options.overlay_scale = OverlayScale::new(0.25).unwrap();
     
     let svg = qr.render_svg(&options);
     save_svg("output/gradient_minimal_logo.svg", &svg);
@@ -3,7 +3,7 @@
 // This example demonstrates creating a stylish QR code with
 // custom colors, shapes, and overlays.
 
-use qrcode_lib::fancy::{FancyQr, FancyOptions, ModuleShape, FinderShape};
+use qrcode_lib::fancy::{FancyQr, FancyOptions, ModuleShape, FinderShape, OverlayScale};
 use std::fs::File;
 use std::io::Write;
 
@@ -14,22 +14,23 @@ fn main() {
         .expect("Failed to create QR code");
     
     // Customize rendering options
-    let mut options = FancyOptions::default();
-    
-    // Set custom colors (purple theme)
-    options.color_background = "#FAF5FF".to_string(); // Light purple background
-    options.color_data = "#6B4B8A".to_string();       // Purple data
-    options.color_finder = "#8B5CF6".to_string();     // Light purple finders
-    
-    // Use circular dots
-    options.shape_module = ModuleShape::Circle;
-    
-    // Use rounded finder patterns
-    options.shape_finder = FinderShape::Rounded(1.5);
-    
-    // Add center text
-    options.center_text = Some("SCAN ME".to_string());
-    options.overlay_scale = 0.2;
+    let options = FancyOptions {
+        // Set custom colors (purple theme)
+        color_background: "#FAF5FF".to_string(), // Light purple background
+        color_data: "#6B4B8A".to_string(),       // Purple data
+        color_finder: "#8B5CF6".to_string(),     // Light purple finders
+        
+        // Use circular dots
+        shape_module: ModuleShape::Circle,
+        
+        // Use rounded finder patterns
+        shape_finder: FinderShape::Rounded(1.5),
+        
+        // Add center text
+        center_text: Some("SCAN ME".to_string()),
+        overlay_scale: OverlayScale::new(0.2).unwrap(),
+        ..FancyOptions::default()
+    };
     
     // Generate SVG
     let svg = qr.render_svg(&options);
@@ -0,0 +1,930 @@
+/* 
+ * QR Code generator library (Rust)
+ * 
+ * Copyright (c) Project Nayuki. (MIT License)
+ * Copyright (c) Abdulrhman Alkhodiry (aalkhodiry@gmail.com)
+ * 
+ * Payload builders for structured QR code content
+ */
+
+//! Payload builders for structured QR code content.
+//! 
+//! These functions produce the text that phones recognize as calendar
+//! entries, payments and similar actions. Encode the result like any
+//! other text, e.g. with `QrCode::encode_text()` or `FancyQr::from_text()`.
+
+pub mod emv;
+
+use emv::TlvBuilder;
+
+/// The error type when content cannot be built from the given fields.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentError {
+    /// A required field is empty (field name)
+    EmptyField(&'static str),
+    /// The address contains characters not valid for the scheme (address)
+    InvalidAddress(String),
+    /// The amount is negative, zero or not finite (amount)
+    InvalidAmount(f64),
+    /// An identifier such as an app id or package name has the wrong format (identifier)
+    InvalidId(String),
+    /// A field is longer than its format allows (field name, maximum length in characters)
+    TooLong(&'static str, usize),
+    /// A timestamp is not of the form `YYYYMMDDTHHMMSSZ` (timestamp)
+    InvalidTimestamp(String),
+    /// The end of an event is before its start
+    EndBeforeStart,
+}
+
+impl std::error::Error for ContentError {}
+
+impl std::fmt::Display for ContentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::EmptyField(name) => write!(f, "Field {} must not be empty", name),
+            Self::InvalidAddress(address) => write!(f, "Invalid address: {}", address),
+            Self::InvalidAmount(amount) => write!(f, "Invalid amount: {}", amount),
+            Self::InvalidId(id) => write!(f, "Invalid identifier: {}", id),
+            Self::TooLong(name, max) => write!(f, "Field {} must be at most {} characters", name, max),
+            Self::InvalidTimestamp(timestamp) => write!(f, "Invalid timestamp: {}", timestamp),
+            Self::EndBeforeStart => write!(f, "End is before start"),
+        }
+    }
+}
+
+/// The cryptocurrency of a payment URI.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CryptoScheme {
+    /// Bitcoin (`bitcoin:`), with a Base58 or `bc1` Bech32 address
+    Bitcoin,
+    /// Ethereum (`ethereum:`), with a `0x`-prefixed 40-digit hex address
+    Ethereum,
+    /// Litecoin (`litecoin:`), with a Base58 or `ltc1` Bech32 address
+    Litecoin,
+}
+
+impl CryptoScheme {
+    /// Returns the URI scheme name, e.g. `"bitcoin"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            CryptoScheme::Bitcoin  => "bitcoin",
+            CryptoScheme::Ethereum => "ethereum",
+            CryptoScheme::Litecoin => "litecoin",
+        }
+    }
+    
+    // Checks the address character set (not its checksum).
+    fn is_valid_address(self, address: &str) -> bool {
+        let bech32 = |hrp: &str| {
+            address.get(..hrp.len()).is_some_and(|p| p.eq_ignore_ascii_case(hrp))
+                && address[hrp.len()..].chars().all(|c| BECH32_CHARSET.contains(c.to_ascii_lowercase()))
+                && (address == address.to_ascii_lowercase() || address == address.to_ascii_uppercase())
+        };
+        let base58 = || address.chars().all(|c| BASE58_CHARSET.contains(c));
+        match self {
+            CryptoScheme::Bitcoin  => bech32("bc1") || base58(),
+            CryptoScheme::Litecoin => bech32("ltc1") || base58(),
+            CryptoScheme::Ethereum => address.len() == 42
+                && address.starts_with("0x")
+                && address[2..].bytes().all(|b| b.is_ascii_hexdigit()),
+        }
+    }
+}
+
+// The characters of Base58 (no 0, O, I or l) and of the Bech32 data part.
+static BASE58_CHARSET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+static BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Returns a payment URI such as `bitcoin:<address>?amount=0.01&label=Donation`,
+/// which wallet apps open as a prefilled payment.
+/// 
+/// The amount is in whole coins (e.g. BTC). The label is percent-encoded.
+/// The address is checked against the scheme's character set, but its
+/// checksum is not verified.
+/// 
+/// Returns `Err` if the address is empty or invalid, or the amount is not positive.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::content::{crypto_payment, CryptoScheme};
+/// 
+/// let uri = crypto_payment(CryptoScheme::Bitcoin, "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+///     Some(0.001), Some("Coffee fund")).unwrap();
+/// assert_eq!(uri, "bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq?amount=0.001&label=Coffee%20fund");
+/// ```
+pub fn crypto_payment(scheme: CryptoScheme, address: &str, amount: Option<f64>, label: Option<&str>) -> Result<String, ContentError> {
+    if address.is_empty() {
+        return Err(ContentError::EmptyField("address"));
+    }
+    if !scheme.is_valid_address(address) {
+        return Err(ContentError::InvalidAddress(address.to_string()));
+    }
+    
+    let mut params = Vec::new();
+    if let Some(amount) = amount {
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(ContentError::InvalidAmount(amount));
+        }
+        params.push(format!("amount={}", amount));
+    }
+    if let Some(label) = label.filter(|l| !l.is_empty()) {
+        params.push(format!("label={}", percent_encode(label)));
+    }
+    
+    let mut uri = format!("{}:{}", scheme.name(), address);
+    if !params.is_empty() {
+        uri.push('?');
+        uri.push_str(&params.join("&"));
+    }
+    Ok(uri)
+}
+
+/// Returns a PIX "copia e cola" payload (the Brazilian Central Bank's instant payment
+/// QR code), an EMV merchant-presented QR code built with `emv::TlvBuilder`.
+/// 
+/// `key` is the receiver's PIX key (e.g. an email address, phone number or random key).
+/// The amount is in BRL and left open to the payer if `None`. An empty `txid` is sent
+/// as `***`, meaning no transaction id.
+/// 
+/// Returns `Err` if the key, merchant name or city is empty or too long (at most 77, 25
+/// and 15 characters), the txid is longer than 25 characters or not alphanumeric, or the
+/// amount is not positive.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::content::pix;
+/// 
+/// let payload = pix("pix@example.com", "Maria Silva", "Sao Paulo", Some(10.5), "").unwrap();
+/// assert!(payload.starts_with("000201"));
+/// assert!(payload.contains("br.gov.bcb.pix"));
+/// ```
+pub fn pix(key: &str, merchant_name: &str, city: &str, amount: Option<f64>, txid: &str) -> Result<String, ContentError> {
+    for (name, value, max) in [("key", key, 77), ("merchant_name", merchant_name, 25), ("city", city, 15)] {
+        if value.is_empty() {
+            return Err(ContentError::EmptyField(name));
+        }
+        if value.chars().count() > max {
+            return Err(ContentError::TooLong(name, max));
+        }
+    }
+    if txid.len() > 25 {
+        return Err(ContentError::TooLong("txid", 25));
+    }
+    if !txid.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err(ContentError::InvalidId(txid.to_string()));
+    }
+    
+    let account = TlvBuilder::new()
+        .add("00", "br.gov.bcb.pix")
+        .add("01", key)
+        .build_template();
+    let mut payload = TlvBuilder::new()
+        .add("00", "01")
+        .add("26", &account)
+        .add("52", "0000")
+        .add("53", "986");
+    if let Some(amount) = amount {
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(ContentError::InvalidAmount(amount));
+        }
+        payload = payload.add("54", &format!("{:.2}", amount));
+    }
+    let additional = TlvBuilder::new()
+        .add("05", if txid.is_empty() { "***" } else { txid })
+        .build_template();
+    Ok(payload
+        .add("58", "BR")
+        .add("59", merchant_name)
+        .add("60", city)
+        .add("62", &additional)
+        .build())
+}
+
+/// Returns the App Store URL of an iOS app, given its numeric app id
+/// (the digits after "id" in the app's store link).
+/// 
+/// Returns `Err` if the id is empty or not all digits.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::content::app_store_ios;
+/// 
+/// assert_eq!(app_store_ios("284882215").unwrap(), "https://apps.apple.com/app/id284882215");
+/// ```
+pub fn app_store_ios(app_id: &str) -> Result<String, ContentError> {
+    if app_id.is_empty() {
+        return Err(ContentError::EmptyField("app_id"));
+    }
+    if !app_id.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ContentError::InvalidId(app_id.to_string()));
+    }
+    Ok(format!("https://apps.apple.com/app/id{}", app_id))
+}
+
+/// Returns the Google Play URL of an Android app, given its package name
+/// (e.g. `com.example.app`).
+/// 
+/// Returns `Err` if the package name is empty or not made of at least two
+/// dot-separated parts that each start with a letter and contain only
+/// letters, digits and underscores.
+pub fn app_store_android(package: &str) -> Result<String, ContentError> {
+    if package.is_empty() {
+        return Err(ContentError::EmptyField("package"));
+    }
+    let valid_part = |part: &str| {
+        part.starts_with(|c: char| c.is_ascii_alphabetic())
+            && part.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+    };
+    if package.split('.').count() < 2 || !package.split('.').all(valid_part) {
+        return Err(ContentError::InvalidId(package.to_string()));
+    }
+    Ok(format!("https://play.google.com/store/apps/details?id={}", package))
+}
+
+/// Returns a deep link for a redirect page that opens the app or falls back to a store.
+/// 
+/// `primary` is the URL of the redirect page (or a universal/app link); the store
+/// fallbacks are appended as percent-encoded `ios` and `android` query parameters,
+/// for the page to pick by platform. An empty fallback is left out.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::content::{deep_link, app_store_ios, app_store_android};
+/// 
+/// let link = deep_link("https://example.com/open",
+///     &app_store_ios("284882215").unwrap(), &app_store_android("com.example.app").unwrap());
+/// assert!(link.starts_with("https://example.com/open?ios=https%3A%2F%2Fapps.apple.com"));
+/// ```
+pub fn deep_link(primary: &str, fallback_ios: &str, fallback_android: &str) -> String {
+    let params: Vec<String> = [("ios", fallback_ios), ("android", fallback_android)].iter()
+        .filter(|(_, url)| !url.is_empty())
+        .map(|(name, url)| format!("{}={}", name, percent_encode(url)))
+        .collect();
+    if params.is_empty() {
+        return primary.to_string();
+    }
+    let separator = if primary.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", primary, separator, params.join("&"))
+}
+
+/// A social network with a public profile URL per handle.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SocialPlatform {
+    /// YouTube channel (`youtube.com/@handle`)
+    YouTube,
+    /// Instagram profile (`instagram.com/handle`)
+    Instagram,
+    /// X, formerly Twitter (`x.com/handle`)
+    X,
+    /// TikTok profile (`tiktok.com/@handle`)
+    TikTok,
+    /// LinkedIn member profile (`linkedin.com/in/handle`)
+    LinkedIn,
+}
+
+impl SocialPlatform {
+    // Returns the profile URL prefix, the punctuation allowed besides ASCII
+    // letters and digits, and the maximum handle length.
+    fn rules(self) -> (&'static str, &'static str, usize) {
+        match self {
+            SocialPlatform::YouTube   => ("https://youtube.com/@", "._-", 30),
+            SocialPlatform::Instagram => ("https://instagram.com/", "._", 30),
+            SocialPlatform::X         => ("https://x.com/", "_", 15),
+            SocialPlatform::TikTok    => ("https://tiktok.com/@", "._", 24),
+            SocialPlatform::LinkedIn  => ("https://linkedin.com/in/", "-", 100),
+        }
+    }
+}
+
+/// Returns the canonical profile URL of `handle` on a social platform.
+/// 
+/// A leading `@` is accepted and dropped. The handle may only contain ASCII
+/// letters, digits and the punctuation the platform allows, up to its length limit.
+/// 
+/// Returns `Err` if the handle is empty or invalid for the platform.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::content::{social, SocialPlatform};
+/// 
+/// assert_eq!(social(SocialPlatform::YouTube, "@example").unwrap(), "https://youtube.com/@example");
+/// ```
+pub fn social(platform: SocialPlatform, handle: &str) -> Result<String, ContentError> {
+    let handle = handle.strip_prefix('@').unwrap_or(handle);
+    if handle.is_empty() {
+        return Err(ContentError::EmptyField("handle"));
+    }
+    let (prefix, punctuation, max_len) = platform.rules();
+    if handle.len() > max_len || !handle.chars().all(|c| c.is_ascii_alphanumeric() || punctuation.contains(c)) {
+        return Err(ContentError::InvalidId(handle.to_string()));
+    }
+    Ok(format!("{}{}", prefix, handle))
+}
+
+/// Returns a `mailto:` URI that opens a new email when scanned, with any number of
+/// `to`, `cc` and `bcc` recipients and an optional subject and body.
+/// 
+/// Recipients are joined with commas; addresses, subject and body are percent-encoded
+/// (RFC 6068). Empty recipient lists, subject and body are left out.
+/// 
+/// Returns `Err` if there are no recipients at all, or if an address has no `@`.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::content::mailto;
+/// 
+/// let uri = mailto(&["sales@example.com"], &["team@example.com"], &[], "Quote request", "").unwrap();
+/// assert_eq!(uri, "mailto:sales@example.com?cc=team@example.com&subject=Quote%20request");
+/// ```
+pub fn mailto(to: &[&str], cc: &[&str], bcc: &[&str], subject: &str, body: &str) -> Result<String, ContentError> {
+    if to.is_empty() && cc.is_empty() && bcc.is_empty() {
+        return Err(ContentError::EmptyField("to"));
+    }
+    let recipients = |addresses: &[&str]| -> Result<String, ContentError> {
+        let encoded = addresses.iter()
+            .map(|address| match address.split_once('@') {
+                Some((local, domain)) if !local.is_empty() && !domain.is_empty() =>
+                    Ok(format!("{}@{}", percent_encode(local), percent_encode(domain))),
+                _ => Err(ContentError::InvalidAddress(address.to_string())),
+            })
+            .collect::<Result<Vec<String>, ContentError>>()?;
+        Ok(encoded.join(","))
+    };
+    
+    let mut params: Vec<String> = Vec::new();
+    for (name, addresses) in [("cc", cc), ("bcc", bcc)] {
+        if !addresses.is_empty() {
+            params.push(format!("{}={}", name, recipients(addresses)?));
+        }
+    }
+    for (name, value) in [("subject", subject), ("body", body)] {
+        if !value.is_empty() {
+            params.push(format!("{}={}", name, percent_encode(value)));
+        }
+    }
+    let mut uri = format!("mailto:{}", recipients(to)?);
+    if !params.is_empty() {
+        uri.push('?');
+        uri.push_str(&params.join("&"));
+    }
+    Ok(uri)
+}
+
+/// The contact format produced by `smart_contact`.
+/// 
+/// MeCard is much shorter, so it gives a smaller, easier to scan code, and Android
+/// scanners and the iOS camera both read it. Some older or third-party readers only
+/// understand vCard, which is longer but the most widely supported format.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Compat {
+    /// A MeCard (`MECARD:...;;`), for the smallest code
+    Compact,
+    /// A minimal vCard 3.0, for the widest reader support
+    Broad,
+}
+
+/// Returns a contact card that phones offer to save (and whose number they offer
+/// to call) when scanned, as a MeCard or a minimal vCard 3.0 per the `compat` hint.
+/// 
+/// An empty phone or email is left out. Values are escaped for the chosen format.
+/// 
+/// Returns `Err` if the name is empty, or if both the phone and email are empty.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::content::{smart_contact, Compat};
+/// 
+/// let card = smart_contact("Jane Doe", "+1 555 0100", "jane@example.com", Compat::Compact).unwrap();
+/// assert_eq!(card, "MECARD:N:Jane Doe;TEL:+1 555 0100;EMAIL:jane@example.com;;");
+/// ```
+pub fn smart_contact(name: &str, phone: &str, email: &str, compat: Compat) -> Result<String, ContentError> {
+    if name.is_empty() {
+        return Err(ContentError::EmptyField("name"));
+    }
+    if phone.is_empty() && email.is_empty() {
+        return Err(ContentError::EmptyField("phone"));
+    }
+    
+    match compat {
+        Compat::Compact => {
+            let mut card = format!("MECARD:N:{};", escape_mecard(name));
+            if !phone.is_empty() {
+                card.push_str(&format!("TEL:{};", escape_mecard(phone)));
+            }
+            if !email.is_empty() {
+                card.push_str(&format!("EMAIL:{};", escape_mecard(email)));
+            }
+            card.push(';');
+            Ok(card)
+        },
+        Compat::Broad => vcard(name, phone, email, None),
+    }
+}
+
+/// The image format of a photo embedded by `vcard`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PhotoFormat {
+    /// A JPEG image
+    Jpeg,
+    /// A PNG image
+    Png,
+}
+
+impl PhotoFormat {
+    /// Returns the vCard `TYPE` parameter value for this format.
+    pub fn type_name(self) -> &'static str {
+        match self {
+            PhotoFormat::Jpeg => "JPEG",
+            PhotoFormat::Png  => "PNG",
+        }
+    }
+}
+
+/// Returns a minimal vCard 3.0 contact card, optionally embedding a photo.
+/// 
+/// The photo bytes are base64 encoded into a `PHOTO;ENCODING=b;TYPE=...` property,
+/// folded into 75-octet lines like every other line of the card. An empty phone
+/// or email is left out.
+/// 
+/// Base64 grows the image by a third, and even a version 40 code holds under 3 KB
+/// of binary data at low error correction, so only tiny thumbnails of a few hundred
+/// bytes leave room for a code that phones can still scan comfortably.
+/// 
+/// Returns `Err` if the name is empty, or if both the phone and email are empty.
+pub fn vcard(name: &str, phone: &str, email: &str, photo: Option<(PhotoFormat, &[u8])>) -> Result<String, ContentError> {
+    if name.is_empty() {
+        return Err(ContentError::EmptyField("name"));
+    }
+    if phone.is_empty() && email.is_empty() {
+        return Err(ContentError::EmptyField("phone"));
+    }
+    
+    let mut lines = vec![
+        "BEGIN:VCARD".to_string(),
+        "VERSION:3.0".to_string(),
+        format!("N:{};;;;", escape_ical_text(name)),
+        format!("FN:{}", escape_ical_text(name)),
+    ];
+    if !phone.is_empty() {
+        lines.push(format!("TEL;TYPE=CELL:{}", escape_ical_text(phone)));
+    }
+    if !email.is_empty() {
+        lines.push(format!("EMAIL:{}", escape_ical_text(email)));
+    }
+    if let Some((format, data)) = photo {
+        lines.push(format!("PHOTO;ENCODING=b;TYPE={}:{}", format.type_name(), crate::render::base64_encode(data)));
+    }
+    lines.push("END:VCARD".to_string());
+    Ok(lines.iter().map(|line| fold_ical_line(line) + "\r\n").collect())
+}
+
+/// The authentication type of a WiFi network.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WifiSecurity {
+    /// WPA, WPA2 or WPA3 personal (`T:WPA`)
+    Wpa,
+    /// WEP (`T:WEP`)
+    Wep,
+    /// An open network without a password (`T:nopass`)
+    Open,
+}
+
+/// The fields of a WiFi network login, as built by `wifi` and read back by `parse_wifi`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WifiConfig {
+    /// The network name
+    pub ssid: String,
+    /// The password; ignored for open networks
+    pub password: String,
+    /// The authentication type
+    pub security: WifiSecurity,
+    /// Whether the network does not broadcast its SSID
+    pub hidden: bool,
+}
+
+/// Returns a WiFi login (`WIFI:T:WPA;S:...;P:...;;`) that phones offer to join when scanned.
+/// 
+/// Values are escaped as in MeCard. The password is left out for open networks, and
+/// `H:true` is added only for hidden networks.
+/// 
+/// Returns `Err` if the SSID is empty, or if the password is empty on a secured network.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::content::{wifi, WifiConfig, WifiSecurity};
+/// 
+/// let config = WifiConfig {
+///     ssid: "Cafe".to_string(),
+///     password: "espresso;2".to_string(),
+///     security: WifiSecurity::Wpa,
+///     hidden: false,
+/// };
+/// assert_eq!(wifi(&config).unwrap(), r"WIFI:T:WPA;S:Cafe;P:espresso\;2;;");
+/// ```
+pub fn wifi(config: &WifiConfig) -> Result<String, ContentError> {
+    if config.ssid.is_empty() {
+        return Err(ContentError::EmptyField("ssid"));
+    }
+    let security = match config.security {
+        WifiSecurity::Wpa => "WPA",
+        WifiSecurity::Wep => "WEP",
+        WifiSecurity::Open => "nopass",
+    };
+    let mut result = format!("WIFI:T:{};S:{};", security, escape_mecard(&config.ssid));
+    if config.security != WifiSecurity::Open {
+        if config.password.is_empty() {
+            return Err(ContentError::EmptyField("password"));
+        }
+        result.push_str(&format!("P:{};", escape_mecard(&config.password)));
+    }
+    if config.hidden {
+        result.push_str("H:true;");
+    }
+    result.push(';');
+    Ok(result)
+}
+
+/// Parses a WiFi login (`WIFI:...;;`) back into its fields, e.g. to edit an existing code.
+/// 
+/// Fields may appear in any order and backslash escapes are undone. A missing `T` field
+/// means an open network; `WPA2` and `WPA3` are read as `WifiSecurity::Wpa`.
+/// 
+/// Returns `None` if the text is not a WiFi login or has no SSID.
+pub fn parse_wifi(s: &str) -> Option<WifiConfig> {
+    let body = s.strip_prefix("WIFI:")?;
+    let mut config = WifiConfig {
+        ssid: String::new(),
+        password: String::new(),
+        security: WifiSecurity::Open,
+        hidden: false,
+    };
+    for field in split_mecard_fields(body) {
+        let (key, value) = match field.split_once(':') {
+            Some(pair) => pair,
+            None if field.is_empty() => continue,
+            None => return None,
+        };
+        let value = unescape_mecard(value);
+        match key {
+            "S" => config.ssid = value,
+            "P" => config.password = value,
+            "T" => config.security = match value.to_ascii_uppercase().as_str() {
+                "WPA" | "WPA2" | "WPA3" => WifiSecurity::Wpa,
+                "WEP" => WifiSecurity::Wep,
+                "NOPASS" | "" => WifiSecurity::Open,
+                _ => return None,
+            },
+            "H" => config.hidden = value.eq_ignore_ascii_case("true"),
+            _ => {},
+        }
+    }
+    if config.ssid.is_empty() {
+        return None;
+    }
+    Some(config)
+}
+
+// Splits MeCard-style content at the semicolons that are not escaped, keeping the escapes.
+fn split_mecard_fields(s: &str) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ';' => {
+                fields.push(&s[start..i]);
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+    fields.push(&s[start..]);
+    fields
+}
+
+// Removes the backslashes added by `escape_mecard`.
+fn unescape_mecard(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+// Escapes the characters with special meaning in MeCard values with a backslash.
+fn escape_mecard(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
+}
+
+// Percent-encodes everything except RFC 3986 unreserved characters.
+fn percent_encode(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+            result.push(char::from(b));
+        } else {
+            result.push_str(&format!("%{:02X}", b));
+        }
+    }
+    result
+}
+
+/// Returns an iCalendar (RFC 5545) document with a single event, which phones
+/// offer to add to the calendar when scanned.
+/// 
+/// Timestamps are UTC in the form `YYYYMMDDTHHMMSSZ`, e.g. `20250314T180000Z`.
+/// Text values are escaped and long lines are folded as RFC 5545 requires.
+/// An empty location or description is left out.
+/// 
+/// Returns `Err` if `start` or `end` is not a timestamp of that form, or if `end`
+/// is before `start`.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::content::calendar_event;
+/// 
+/// let event = calendar_event("Launch party", "20250314T180000Z", "20250314T220000Z",
+///     "Main Hall", "Drinks, music; fun").unwrap();
+/// assert!(event.contains("DESCRIPTION:Drinks\\, music\\; fun\r\n"));
+/// ```
+pub fn calendar_event(summary: &str, start: &str, end: &str, location: &str, description: &str) -> Result<String, ContentError> {
+    for timestamp in [start, end] {
+        if !is_utc_timestamp(timestamp) {
+            return Err(ContentError::InvalidTimestamp(timestamp.to_string()));
+        }
+    }
+    // The fixed-width form sorts chronologically
+    if end < start {
+        return Err(ContentError::EndBeforeStart);
+    }
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//qrcode-lib//EN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("SUMMARY:{}", escape_ical_text(summary)),
+        format!("DTSTART:{}", start),
+        format!("DTEND:{}", end),
+    ];
+    if !location.is_empty() {
+        lines.push(format!("LOCATION:{}", escape_ical_text(location)));
+    }
+    if !description.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape_ical_text(description)));
+    }
+    lines.push("END:VEVENT".to_string());
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(lines.iter().map(|line| fold_ical_line(line) + "\r\n").collect())
+}
+
+// Tests whether the string is a UTC date-time of the form YYYYMMDDTHHMMSSZ.
+fn is_utc_timestamp(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 16
+        && b[..8].iter().all(u8::is_ascii_digit)
+        && b[8] == b'T'
+        && b[9..15].iter().all(u8::is_ascii_digit)
+        && b[15] == b'Z'
+}
+
+// Escapes backslashes, semicolons, commas and newlines in an iCalendar (or vCard) TEXT value.
+fn escape_ical_text(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            ';' => result.push_str("\\;"),
+            ',' => result.push_str("\\,"),
+            '\n' => result.push_str("\\n"),
+            '\r' => {},
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+// Folds a content line into chunks of at most 75 octets, continuing each with CRLF
+// and a space. Never splits a UTF-8 character.
+fn fold_ical_line(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > 75 {
+            result.push_str("\r\n ");
+            octets = 1;
+        }
+        result.push(c);
+        octets += c.len_utf8();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendar_event() {
+        let event = calendar_event("Meetup, Berlin", "20250314T180000Z", "20250314T200000Z", "", "Line 1\nLine 2").unwrap();
+        assert!(event.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(event.contains("\r\nBEGIN:VEVENT\r\n"));
+        assert!(event.contains("\r\nSUMMARY:Meetup\\, Berlin\r\n"));
+        assert!(event.contains("\r\nDTSTART:20250314T180000Z\r\nDTEND:20250314T200000Z\r\n"));
+        assert!(event.contains("\r\nDESCRIPTION:Line 1\\nLine 2\r\n"));
+        assert!(!event.contains("LOCATION"));
+        assert!(event.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn test_calendar_line_folding() {
+        let summary = "é".repeat(60);
+        let event = calendar_event(&summary, "20250314T180000Z", "20250314T200000Z", "", "").unwrap();
+        for line in event.split("\r\n") {
+            assert!(line.len() <= 75);
+        }
+        let unfolded = event.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!("SUMMARY:{}\r\n", summary)));
+    }
+
+    #[test]
+    fn test_crypto_payment() {
+        let address = "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2";
+        assert_eq!(crypto_payment(CryptoScheme::Bitcoin, address, Some(0.5), Some("Tip jar")).unwrap(),
+            "bitcoin:1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2?amount=0.5&label=Tip%20jar");
+        assert_eq!(crypto_payment(CryptoScheme::Bitcoin, address, None, Some("Café & Co")).unwrap(),
+            "bitcoin:1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2?label=Caf%C3%A9%20%26%20Co");
+        assert_eq!(crypto_payment(CryptoScheme::Bitcoin, address, None, None).unwrap(),
+            "bitcoin:1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2");
+        
+        let eth = "0x52908400098527886E0F7030069857D2E4169EE7";
+        assert!(crypto_payment(CryptoScheme::Ethereum, eth, Some(1.0), None).unwrap().starts_with("ethereum:0x5290"));
+        assert_eq!(crypto_payment(CryptoScheme::Ethereum, &eth[..41], None, None),
+            Err(ContentError::InvalidAddress(eth[..41].to_string())));
+        
+        assert_eq!(crypto_payment(CryptoScheme::Bitcoin, "", None, None), Err(ContentError::EmptyField("address")));
+        assert_eq!(crypto_payment(CryptoScheme::Bitcoin, "1Bv0OIl", None, None),
+            Err(ContentError::InvalidAddress("1Bv0OIl".to_string())));
+        assert_eq!(crypto_payment(CryptoScheme::Bitcoin, address, Some(-1.0), None), Err(ContentError::InvalidAmount(-1.0)));
+    }
+    
+    #[test]
+    fn test_calendar_bad_timestamp() {
+        assert_eq!(calendar_event("X", "2025-03-14 18:00", "20250314T200000Z", "", ""),
+            Err(ContentError::InvalidTimestamp("2025-03-14 18:00".to_string())));
+        assert_eq!(calendar_event("X", "20250314T180000Z", "20250314T2000Z", "", ""),
+            Err(ContentError::InvalidTimestamp("20250314T2000Z".to_string())));
+        assert_eq!(calendar_event("X", "20250314T200000Z", "20250314T180000Z", "", ""), Err(ContentError::EndBeforeStart));
+        assert!(calendar_event("X", "20250314T180000Z", "20250314T180000Z", "", "").is_ok());
+    }
+    
+    #[test]
+    fn test_app_store_links() {
+        assert_eq!(app_store_ios("284882215").unwrap(), "https://apps.apple.com/app/id284882215");
+        assert_eq!(app_store_ios("id284882215"), Err(ContentError::InvalidId("id284882215".to_string())));
+        assert_eq!(app_store_ios(""), Err(ContentError::EmptyField("app_id")));
+        
+        assert_eq!(app_store_android("com.example.my_app").unwrap(),
+            "https://play.google.com/store/apps/details?id=com.example.my_app");
+        for bad in ["example", "com.1example", "com..app", "com.example-app"] {
+            assert_eq!(app_store_android(bad), Err(ContentError::InvalidId(bad.to_string())));
+        }
+    }
+    
+    #[test]
+    fn test_deep_link() {
+        assert_eq!(deep_link("https://example.com/open?ref=qr", "https://apps.apple.com/app/id1", ""),
+            "https://example.com/open?ref=qr&ios=https%3A%2F%2Fapps.apple.com%2Fapp%2Fid1");
+        assert_eq!(deep_link("myapp://home", "", ""), "myapp://home");
+    }
+    
+    #[test]
+    fn test_smart_contact() {
+        let card = smart_contact("Doe, Jane", "+15550100", "", Compat::Compact).unwrap();
+        assert_eq!(card, "MECARD:N:Doe\\, Jane;TEL:+15550100;;");
+        
+        let card = smart_contact("Doe, Jane", "+15550100", "jane@example.com", Compat::Broad).unwrap();
+        assert!(card.starts_with("BEGIN:VCARD\r\nVERSION:3.0\r\n"));
+        assert!(card.contains("\r\nFN:Doe\\, Jane\r\n"));
+        assert!(card.contains("\r\nTEL;TYPE=CELL:+15550100\r\nEMAIL:jane@example.com\r\n"));
+        assert!(card.ends_with("END:VCARD\r\n"));
+        
+        assert_eq!(smart_contact("", "+15550100", "", Compat::Broad), Err(ContentError::EmptyField("name")));
+        assert_eq!(smart_contact("Jane", "", "", Compat::Compact), Err(ContentError::EmptyField("phone")));
+    }
+    
+    #[test]
+    fn test_vcard_photo() {
+        let photo: Vec<u8> = (0u8 .. 100).collect();
+        let card = vcard("Jane Doe", "+15550100", "", Some((PhotoFormat::Jpeg, &photo))).unwrap();
+        assert!(card.ends_with("\r\nEND:VCARD\r\n"));
+        
+        let start = card.find("PHOTO;ENCODING=b;TYPE=JPEG:").unwrap();
+        let end = card.find("END:VCARD").unwrap();
+        let folded: Vec<&str> = card[start .. end].split_terminator("\r\n").collect();
+        assert_eq!(folded.len(), 3);
+        assert!(folded.iter().all(|line| line.len() <= 75));
+        assert!(folded[1 ..].iter().all(|line| line.starts_with(' ')));
+        let unfolded: String = folded.iter().enumerate()
+            .map(|(i, line)| if i == 0 { *line } else { &line[1 ..] })
+            .collect();
+        assert_eq!(unfolded, format!("PHOTO;ENCODING=b;TYPE=JPEG:{}", crate::render::base64_encode(&photo)));
+        
+        assert_eq!(vcard("Jane Doe", "+15550100", "", None).unwrap(),
+            smart_contact("Jane Doe", "+15550100", "", Compat::Broad).unwrap());
+    }
+    
+    #[test]
+    fn test_wifi_round_trip() {
+        let config = WifiConfig {
+            ssid: r#"Home "5G", upstairs"#.to_string(),
+            password: r"p;ss:wo\rd".to_string(),
+            security: WifiSecurity::Wpa,
+            hidden: true,
+        };
+        let payload = wifi(&config).unwrap();
+        assert_eq!(payload, r#"WIFI:T:WPA;S:Home \"5G\"\, upstairs;P:p\;ss\:wo\\rd;H:true;;"#);
+        assert_eq!(parse_wifi(&payload), Some(config));
+        
+        let open = WifiConfig { ssid: "Guest".to_string(), password: String::new(), security: WifiSecurity::Open, hidden: false };
+        assert_eq!(wifi(&open).unwrap(), "WIFI:T:nopass;S:Guest;;");
+        assert_eq!(parse_wifi("WIFI:S:Guest;;"), Some(open));
+        assert_eq!(parse_wifi("WIFI:P:secret;T:WPA2;S:Office;;").unwrap().security, WifiSecurity::Wpa);
+        
+        assert_eq!(parse_wifi("MECARD:N:Jane;;"), None);
+        assert_eq!(parse_wifi("WIFI:T:WPA;P:secret;;"), None);
+        assert_eq!(wifi(&WifiConfig { password: String::new(), security: WifiSecurity::Wep, ssid: "X".to_string(), hidden: false }),
+            Err(ContentError::EmptyField("password")));
+    }
+    
+    #[test]
+    fn test_mailto() {
+        let uri = mailto(&["ann@example.com", "bob@example.com"], &["shared@example.com"], &[], "", "").unwrap();
+        assert_eq!(uri, "mailto:ann@example.com,bob@example.com?cc=shared@example.com");
+        
+        let uri = mailto(&["a+b@example.com"], &[], &["audit@example.com"], "Hi & bye", "Line 1\nLine 2").unwrap();
+        assert_eq!(uri, "mailto:a%2Bb@example.com?bcc=audit@example.com&subject=Hi%20%26%20bye&body=Line%201%0ALine%202");
+        
+        // Only a cc is still a valid mailto
+        assert_eq!(mailto(&[], &["shared@example.com"], &[], "", "").unwrap(), "mailto:?cc=shared@example.com");
+        assert_eq!(mailto(&[], &[], &[], "Subject", ""), Err(ContentError::EmptyField("to")));
+        assert_eq!(mailto(&["nobody"], &[], &[], "", ""), Err(ContentError::InvalidAddress("nobody".to_string())));
+    }
+    
+    #[test]
+    fn test_social() {
+        assert_eq!(social(SocialPlatform::Instagram, "user.name").unwrap(), "https://instagram.com/user.name");
+        assert_eq!(social(SocialPlatform::X, "@jack").unwrap(), "https://x.com/jack");
+        assert_eq!(social(SocialPlatform::TikTok, "user.name").unwrap(), "https://tiktok.com/@user.name");
+        assert_eq!(social(SocialPlatform::LinkedIn, "jane-doe").unwrap(), "https://linkedin.com/in/jane-doe");
+        assert_eq!(social(SocialPlatform::Instagram, "user name"), Err(ContentError::InvalidId("user name".to_string())));
+        assert_eq!(social(SocialPlatform::X, "user.name"), Err(ContentError::InvalidId("user.name".to_string())));
+        assert_eq!(social(SocialPlatform::X, "a_very_long_handle"), Err(ContentError::InvalidId("a_very_long_handle".to_string())));
+        assert_eq!(social(SocialPlatform::YouTube, "@"), Err(ContentError::EmptyField("handle")));
+    }
+    
+    #[test]
+    fn test_pix() {
+        let payload = pix("pix@example.com", "Maria Silva", "Sao Paulo", Some(10.5), "TX123").unwrap();
+        assert!(payload.starts_with("000201"));
+        assert!(payload.contains("26370014br.gov.bcb.pix0115pix@example.com"));
+        assert!(payload.contains("540510.50"));
+        assert!(payload.contains("5911Maria Silva6009Sao Paulo62090505TX123"));
+        let (body, crc) = payload.split_at(payload.len() - 4);
+        assert!(body.ends_with("6304"));
+        assert!(crc.bytes().all(|b| b.is_ascii_hexdigit() && !b.is_ascii_lowercase()));
+        assert_eq!(u16::from_str_radix(crc, 16).unwrap(), emv::crc16_ccitt(body.as_bytes()));
+        
+        let open = pix("pix@example.com", "Maria Silva", "Sao Paulo", None, "").unwrap();
+        assert!(!open.contains("5405") && open.contains("62070503***"));
+        
+        assert_eq!(pix("k", "A name longer than twenty-five", "Rio", None, ""), Err(ContentError::TooLong("merchant_name", 25)));
+        assert_eq!(pix("k", "Maria", "Rio de Janeiro RJ", None, ""), Err(ContentError::TooLong("city", 15)));
+        assert_eq!(pix("", "Maria", "Rio", None, ""), Err(ContentError::EmptyField("key")));
+        assert_eq!(pix("k", "Maria", "Rio", None, "TX-1"), Err(ContentError::InvalidId("TX-1".to_string())));
+        assert_eq!(pix("k", "Maria", "Rio", Some(0.0), ""), Err(ContentError::InvalidAmount(0.0)));
+    }
+}
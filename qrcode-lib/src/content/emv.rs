@@ -0,0 +1,115 @@
+/* 
+ * QR Code generator library (Rust)
+ * 
+ * Copyright (c) Project Nayuki. (MIT License)
+ * Copyright (c) Abdulrhman Alkhodiry (aalkhodiry@gmail.com)
+ * 
+ * EMVCo merchant-presented QR code payloads
+ */
+
+//! EMVCo merchant-presented QR code payloads.
+//! 
+//! PIX, and several other national payment schemes, encode their payloads as
+//! EMVCo tag-length-value fields ending in a CRC16 checksum in tag 63.
+//! `TlvBuilder` assembles such payloads for any of these schemes.
+
+/// Assembles EMVCo tag-length-value fields, in the order they are added.
+/// 
+/// Each field is written as its 2-digit tag, its value length as 2 digits and the value.
+/// Nested templates (e.g. a merchant account information field) are built with
+/// `build_template()` and added as the value of their parent tag.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::content::emv::TlvBuilder;
+/// 
+/// let account = TlvBuilder::new().add("00", "br.gov.bcb.pix").add("01", "pix@example.com").build_template();
+/// let payload = TlvBuilder::new().add("00", "01").add("26", &account).build();
+/// assert!(payload.starts_with("00020126370014br.gov.bcb.pix"));
+/// assert!(payload[payload.len() - 8 ..].starts_with("6304"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TlvBuilder {
+    fields: String,
+}
+
+impl TlvBuilder {
+    /// Returns a builder without any fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Returns this builder with the given field appended.
+    /// 
+    /// Panics if the tag is not two ASCII digits, or the value is longer than
+    /// 99 characters, the most a 2-digit length can express.
+    pub fn add(mut self, tag: &str, value: &str) -> Self {
+        assert!(tag.len() == 2 && tag.bytes().all(|b| b.is_ascii_digit()), "Invalid tag");
+        let len = value.chars().count();
+        assert!(len <= 99, "Value too long");
+        self.fields.push_str(&format!("{}{:02}{}", tag, len, value));
+        self
+    }
+    
+    /// Returns the fields without a checksum, as the value of a nested template.
+    pub fn build_template(self) -> String {
+        self.fields
+    }
+    
+    /// Returns the complete payload: the fields followed by tag 63 with the
+    /// CRC16-CCITT checksum (4 uppercase hex digits) of everything before it,
+    /// including the tag 63 header itself.
+    pub fn build(self) -> String {
+        let mut payload = self.fields;
+        payload.push_str("6304");
+        let crc = crc16_ccitt(payload.as_bytes());
+        payload.push_str(&format!("{:04X}", crc));
+        payload
+    }
+}
+
+/// Returns the CRC-16/CCITT-FALSE checksum (polynomial 0x1021, initial value 0xFFFF)
+/// that EMVCo payloads carry in tag 63, e.g. to verify a scanned payload.
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &b in data {
+        crc ^= u16::from(b) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_tlv_lengths() {
+        let fields = TlvBuilder::new().add("00", "01").add("58", "BR").add("59", "Loja").add("60", &"x".repeat(15))
+            .build_template();
+        assert_eq!(fields, format!("0002015802BR5904Loja6015{}", "x".repeat(15)));
+        assert_eq!(TlvBuilder::new().add("62", "").build_template(), "6200");
+        // Lengths count characters, not bytes
+        assert_eq!(TlvBuilder::new().add("59", "São").build_template(), "5903São");
+        assert_eq!(TlvBuilder::new().add("01", &"9".repeat(99)).build_template().len(), 103);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Value too long")]
+    fn test_tlv_value_too_long() {
+        let _ = TlvBuilder::new().add("01", &"9".repeat(100));
+    }
+    
+    #[test]
+    fn test_crc16() {
+        // The standard check value of CRC-16/CCITT-FALSE
+        assert_eq!(crc16_ccitt(b"123456789"), 0x29B1);
+        assert_eq!(TlvBuilder::new().build(), format!("6304{:04X}", crc16_ccitt(b"6304")));
+        let payload = TlvBuilder::new().add("00", "01").build();
+        assert_eq!(&payload[.. 10], "0002016304");
+        assert_eq!(payload[10 ..], format!("{:04X}", crc16_ccitt(b"0002016304")));
+    }
+}
@@ -9,8 +9,112 @@
 
 //! Fancy QR code rendering with custom styles, colors, and overlays.
 
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex, OnceLock};
+
 use crate::qrcode::QrCode;
-use crate::types::{QrCodeEcc, DataTooLong};
+use crate::render::{fmt_coord, Rotation};
+use crate::segment::QrSegment;
+use crate::types::{QrCodeEcc, DataTooLong, Version};
+
+/// The size of the center safe zone, as a fraction of the matrix width.
+/// 
+/// Values above 0.3 cover more modules than High error correction can recover,
+/// so construction is limited to the range `0.0..=0.3`.
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub struct OverlayScale(f32);
+
+impl OverlayScale {
+    /// No safe zone at all.
+    pub const NONE: OverlayScale = OverlayScale(0.0);
+    
+    /// The largest safe zone that High error correction can typically recover from.
+    pub const MAX: OverlayScale = OverlayScale(0.3);
+    
+    /// Creates an overlay scale from the given fraction.
+    /// 
+    /// Returns `Err` if the value is outside the range `0.0..=0.3` (or is NaN).
+    pub fn new(scale: f32) -> Result<Self, InvalidOverlayScale> {
+        if (Self::NONE.0 ..= Self::MAX.0).contains(&scale) {
+            Ok(Self(scale))
+        } else {
+            Err(InvalidOverlayScale(scale))
+        }
+    }
+    
+    /// Creates an overlay scale, clamping the given fraction into the range `0.0..=0.3`.
+    /// 
+    /// NaN is treated as 0.0.
+    pub fn clamped(scale: f32) -> Self {
+        if scale.is_nan() {
+            Self::NONE
+        } else {
+            Self(scale.clamp(Self::NONE.0, Self::MAX.0))
+        }
+    }
+    
+    /// Returns the value, which is in the range `0.0..=0.3`.
+    pub fn value(self) -> f32 {
+        self.0
+    }
+}
+
+impl Default for OverlayScale {
+    fn default() -> Self {
+        Self(0.2)
+    }
+}
+
+/// The error type when an overlay scale is outside the range `0.0..=0.3`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidOverlayScale(pub f32);
+
+impl std::error::Error for InvalidOverlayScale {}
+
+impl std::fmt::Display for InvalidOverlayScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Overlay scale {} is outside the range 0.0 to 0.3", self.0)
+    }
+}
+
+/// The outline of a center image overlay.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum OverlayShape {
+    /// The image fills the square safe zone, cropped to it
+    #[default]
+    Square,
+    /// The image is clipped to the circle inscribed in the safe zone
+    Circle,
+}
+
+/// The error type when a style spec passed to `FancyOptions::parse` is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseStyleError {
+    /// A comma-separated item that is not of the form `key=value`
+    Syntax(String),
+    /// A key that names no option
+    UnknownKey(String),
+    /// A value that is not a `#RGB` or `#RRGGBB` color
+    InvalidColor(String),
+    /// A module or finder shape name that is not recognized
+    UnknownShape(String),
+    /// A number that does not parse or is outside the option's range
+    OutOfRange(String),
+}
+
+impl std::error::Error for ParseStyleError {}
+
+impl std::fmt::Display for ParseStyleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Syntax(s) => write!(f, "Expected key=value, got {:?}", s),
+            Self::UnknownKey(s) => write!(f, "Unknown style key: {:?}", s),
+            Self::InvalidColor(s) => write!(f, "Invalid color: {:?}", s),
+            Self::UnknownShape(s) => write!(f, "Unknown shape: {:?}", s),
+            Self::OutOfRange(s) => write!(f, "Value out of range: {:?}", s),
+        }
+    }
+}
 
 /// Controls the shape of the small data dots.
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -21,6 +125,25 @@ pub enum ModuleShape {
     Circle,
     /// A square with rounded corners. Radius is 0.0 to 0.5 (relative to module size).
     RoundedSquare(f32), 
+    /// Vertically adjacent data modules joined into one bar per run.
+    /// Radius is 0.0 to 0.5 (relative to module size); 0.5 gives pills.
+    VerticalBars {
+        /// Corner radius of each bar
+        radius: f32,
+    },
+    /// Horizontally adjacent data modules joined into one bar per run.
+    /// Radius is 0.0 to 0.5 (relative to module size); 0.5 gives pills.
+    HorizontalBars {
+        /// Corner radius of each bar
+        radius: f32,
+    },
+    /// A superellipse `|x|^n + |y|^n = 1` filling the module, drawn with cubic Béziers.
+    /// `n` is clamped to 1.0 (a diamond) to 5.0; 2.0 is a circle and about 4.0 the
+    /// classic iOS-style squircle.
+    Squircle {
+        /// Exponent of the superellipse
+        n: f32,
+    },
 }
 
 /// Controls the shape of the 3 large corner patterns.
@@ -30,6 +153,202 @@ pub enum FinderShape {
     Square,
     /// Rounded corners. Radius is relative to the 7-module width.
     Rounded(f32), 
+    /// Fully circular frame or ball
+    Dot,
+}
+
+/// The order in which data modules are revealed by a staggered animation.
+/// 
+/// See `FancyOptions::reveal_order`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RevealOrder {
+    /// Row by row from the top, each row left to right
+    Raster,
+    /// Outward from the center, ring by ring, clockwise within each ring
+    Spiral,
+    /// A shuffled order that is reproducible for the same seed
+    Random(u64),
+}
+
+/// The direction a linear gradient sweeps across the QR matrix.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GradientDirection {
+    /// Left to right
+    Horizontal,
+    /// Top to bottom
+    Vertical,
+    /// Top-left corner to bottom-right corner
+    DiagonalTLBR,
+    /// Top-right corner to bottom-left corner
+    DiagonalTRBL,
+}
+
+impl GradientDirection {
+    // Returns the (x1, y1, x2, y2) endpoints for a square region at (origin, origin) of the given width.
+    pub(crate) fn endpoints(self, origin: f32, width: f32) -> (f32, f32, f32, f32) {
+        let (a, b) = (origin, origin + width);
+        match self {
+            GradientDirection::Horizontal   => (a, a, b, a),
+            GradientDirection::Vertical     => (a, a, a, b),
+            GradientDirection::DiagonalTLBR => (a, a, b, b),
+            GradientDirection::DiagonalTRBL => (b, a, a, b),
+        }
+    }
+}
+
+/// A decoration drawn in the quiet zone, e.g. for stickers.
+/// 
+/// Decorations stay within the outermost module ring of the quiet zone, so the rest
+/// of it remains clear. `FancyQr::validate` counts that ring as used when checking
+/// the quiet zone width.
+#[derive(Clone, PartialEq, Debug)]
+pub enum QuietZoneDecoration {
+    /// A thin rounded frame along the outer edge of the quiet zone
+    InnerLine {
+        /// Line color (hex format, e.g., "#CCCCCC")
+        color: String,
+        /// Line width in modules, at most 1.0
+        width: f32,
+    },
+    /// A dotted frame, one small dot per module along the outer edge of the quiet zone
+    Dots {
+        /// Dot color (hex format, e.g., "#CCCCCC")
+        color: String,
+    },
+}
+
+/// A two-color linear gradient spanning the whole QR matrix.
+/// 
+/// The gradient is laid out in the symbol's coordinate space, so data modules and
+/// finder patterns that share a gradient form one continuous sweep.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Gradient {
+    /// Start color (hex format, e.g., "#4d3695")
+    pub start: String,
+    /// End color (hex format, e.g., "#3B82F6")
+    pub end: String,
+    /// Direction of the sweep
+    pub direction: GradientDirection,
+}
+
+/// Text set along a circle surrounding the code (e.g., "SCAN TO PAY").
+/// 
+/// The canvas grows so the whole ring fits; the code itself stays square in the middle.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RingText {
+    /// The caption to set along the ring
+    pub text: String,
+    /// Text color (hex format, e.g., "#000000")
+    pub color: String,
+    /// CSS font family (e.g., "sans-serif")
+    pub font: String,
+    /// Radius of the text baseline in modules, measured from the center of the code.
+    /// Values too small to clear the code's corners are raised to the smallest radius that does.
+    pub radius: f32,
+}
+
+// Returns the Bézier handle length of a superellipse quadrant with exponent `n`, as a
+// fraction of its radius. The handles are chosen so the curve passes through the
+// superellipse's 45° point (0.552 for a circle).
+pub(crate) fn squircle_handle(n: f32) -> f32 {
+    (8.0 * 2f32.powf(-1.0 / n.clamp(1.0, 5.0)) - 4.0) / 3.0
+}
+
+// Returns the palette color of the data element at (column, row), if a palette is set.
+// Finder modules drawn as data modules (without `custom_finders`) get none.
+pub(crate) fn palette_color(options: &FancyOptions, c: usize, r: usize, width: usize) -> Option<&str> {
+    let (colors, seed) = options.palette.as_ref().filter(|(colors, _)| !colors.is_empty())?;
+    if FancyQr::is_finder_module(c, r, width) {
+        return None;
+    }
+    // SplitMix64 finalizer over the seed and the position
+    let mut h = seed ^ ((c as u64) << 32 | r as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^= h >> 31;
+    Some(&colors[(h % colors.len() as u64) as usize])
+}
+
+// Converts a position in modules to SVG user units, rounded to an integer when snapping.
+fn snap_units(v: f32, snap: Option<NonZeroU32>) -> f32 {
+    match snap {
+        Some(unit) => (v * unit.get() as f32).round(),
+        None => v,
+    }
+}
+
+// Formats a position in modules as SVG user units, with at most 3 decimals.
+fn snap_px(v: f32, snap: Option<NonZeroU32>) -> String {
+    fmt_coord(snap_units(v, snap))
+}
+
+// Formats a size or radius in modules as SVG user units like `snap_px`, but never rounds
+// a positive length down to 0, so small circles and corner radii stay visible.
+fn snap_len(v: f32, snap: Option<NonZeroU32>) -> String {
+    match snap_units(v, snap) {
+        len if v > 0.0 && snap.is_some() => fmt_coord(len.max(1.0)),
+        len => fmt_coord(len),
+    }
+}
+
+// Formats the distance between two positions in modules once both are snapped.
+fn snap_span(from: f32, to: f32, snap: Option<NonZeroU32>) -> String {
+    fmt_coord(snap_units(to, snap) - snap_units(from, snap))
+}
+
+// Formats the `data-x` and `data-y` debug attributes of an element at a module, with a leading space.
+fn debug_attrs(c: usize, r: usize) -> String {
+    format!(r#" data-x="{}" data-y="{}""#, c, r)
+}
+
+impl RingText {
+    // The baseline radius actually used for a matrix of the given width.
+    fn effective_radius(&self, matrix_width: usize) -> f32 {
+        self.radius.max(matrix_width as f32 * std::f32::consts::FRAC_1_SQRT_2 + 0.5)
+    }
+    
+    // Font size in modules, proportional to the ring.
+    fn font_size(radius: f32) -> f32 {
+        radius * 0.12
+    }
+}
+
+/// A preset combination of colors, shapes and overlay size.
+/// 
+/// Use `FancyOptions::from_theme` to start from a preset, then override
+/// individual fields or colors with `FancyOptions::with_colors`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Theme {
+    /// Purple rounded modules and finders, with a large logo area
+    Standard,
+    /// Black square modules with purple rounded finders and a logo area
+    MinimalLogo,
+    /// Purple circles on a lavender background, with a logo area
+    GradientLogo,
+    /// Purple rounded modules with strongly rounded finders and a logo area
+    Premium,
+    /// Near-black rounded modules with prominent purple finders, no logo area
+    BrandedFinders,
+    /// Black square modules with purple rounded finders, no logo area
+    MinimalFinders,
+    /// Muted purple circles with heavily rounded finders, no logo area
+    GradientFinders,
+    /// Muted purple squares on a lavender background, with a logo area
+    GradientMinimal,
+}
+
+impl Theme {
+    /// All themes, in presentation order.
+    pub const ALL: [Theme; 8] = [
+        Theme::Standard,
+        Theme::MinimalLogo,
+        Theme::GradientLogo,
+        Theme::Premium,
+        Theme::BrandedFinders,
+        Theme::MinimalFinders,
+        Theme::GradientFinders,
+        Theme::GradientMinimal,
+    ];
 }
 
 /// Configuration options for fancy QR code rendering.
@@ -40,11 +359,40 @@ pub struct FancyOptions {
     pub color_data: String,
     /// Finder pattern color (hex format, e.g., "#000000")
     pub color_finder: String,
+    /// Gradient for the data modules, replacing `color_data` when set
+    pub gradient_data: Option<Gradient>,
+    /// Gradient for the finder frames and balls, replacing `color_finder` when set.
+    /// Use the same value as `gradient_data` to sweep one gradient across the whole code.
+    pub gradient_finder: Option<Gradient>,
     
     /// Shape of the data modules
     pub shape_module: ModuleShape,
+    /// Per-module shape override, called with the (column, row) of each drawn data
+    /// module; replaces `shape_module` when set. Bars join only neighbors that are
+    /// bars of the same direction.
+    pub module_shape_fn: Option<Box<dyn Fn(usize, usize) -> ModuleShape>>,
+    /// Per-module corner radius, called with the (column, row) of each drawn
+    /// `RoundedSquare` data module; replaces its radius when set. Clamped to 0.0 to 0.5.
+    pub corner_radius_map: Option<Box<dyn Fn(usize, usize) -> f32>>,
     /// Shape of the finder patterns
     pub shape_finder: FinderShape,
+    /// Shape of the 3x3 finder center ("ball"). `None` matches `shape_finder`,
+    /// so e.g. a square frame can be combined with a `FinderShape::Dot` ball.
+    pub finder_ball_shape: Option<FinderShape>,
+    /// Thickness of the finder frame in modules, i.e. the inset of the light cutout
+    /// (standard 1.0). Clamped to 0.5 to 1.5; the 7-module footprint is kept.
+    pub finder_inner_padding: f32,
+    /// Size of the finder ball relative to the standard 3 modules (standard 1.0).
+    /// Clamped to at least 0.5, and so that a light gap of half a module remains.
+    pub finder_ball_scale: f32,
+    /// Colors of concentric finder rings, outermost first (e.g., `["#000", "#FFF", "#F00", "#FFF"]`).
+    /// When set, replaces the standard frame/cutout/ball structure with that many
+    /// evenly nested rings inside the 7-module footprint.
+    pub finder_rings: Option<Vec<String>>,
+    /// Draw the stylized finder patterns (`shape_finder`, `finder_ball_shape`, `finder_rings`
+    /// and the finder color). When false, finder modules are drawn like data modules,
+    /// in the data shape and fill, for maximal scannability.
+    pub custom_finders: bool,
     
     /// URL or Base64 data for a center image overlay
     pub center_image_url: Option<String>,
@@ -52,7 +400,63 @@ pub struct FancyOptions {
     pub center_text: Option<String>,
     /// How large the center safe zone is (0.0 to 0.3).
     /// Note: Error correction High can typically recover up to 30% damage.
-    pub overlay_scale: f32,
+    pub overlay_scale: OverlayScale,
+    /// Outline of the center image. The cleared safe zone stays square either way.
+    pub overlay_shape: OverlayShape,
+    /// A translucent panel (color, opacity from 0.0 to 1.0) behind the center overlay.
+    /// When set, the safe zone is not cleared: its modules are drawn and tinted by the
+    /// panel, so the overlay appears to sit under glass. The overlay still hides modules
+    /// and the tint lowers their contrast, so this leaves error correction less margin
+    /// than a cleared safe zone; use High ECC and a low opacity. `FancyQr::validate`
+    /// counts the modules under the overlay as hidden either way.
+    pub overlay_glass: Option<(String, f32)>,
+    /// Color of the data modules bordering the safe zone (within one module of it),
+    /// framing the overlay. `None` draws them like any other data module.
+    pub ring_data_color: Option<String>,
+    /// Colors for the data modules (colors, seed): each drawn data element takes a color
+    /// picked by a hash of the seed and its position, so the same seed always gives the
+    /// same coloring. Replaces `color_data` and `gradient_data`; finders keep their color.
+    pub palette: Option<(Vec<String>, u64)>,
+    /// Dots (color, radius in modules, clamped to 0.0 to 0.5) centered in the light cells
+    /// of the data region, for a full-bleed look. Function patterns, the quiet zone and the
+    /// safe zone stay clear; use a faint color so the dots don't read as dark modules.
+    pub background_dots: Option<(String, f32)>,
+    
+    /// Caption set along a circle around the code
+    pub ring_text: Option<RingText>,
+    
+    /// Pattern or frame drawn in the outermost ring of the quiet zone
+    pub quiet_zone_decoration: Option<QuietZoneDecoration>,
+    
+    /// Draw the timing patterns (the alternating lines between finders) as plain squares
+    /// regardless of `shape_module`, so they stay unambiguous for picky scanners.
+    pub solid_timing: bool,
+    
+    /// Emit integer coordinates with this many SVG user units per module.
+    /// Keeps edges on the pixel grid for rasterizers that blur fractional coordinates.
+    /// `None` keeps the default of one unit per module. Positions and edges are rounded;
+    /// sizes and radii are rounded too, but never down to 0.
+    pub pixel_snap: Option<NonZeroU32>,
+    
+    /// Number the drawn data elements in this order for a staggered reveal animation.
+    /// Each gets a `data-reveal` index (0 first) and an `animation-delay` of that many
+    /// steps of the CSS variable `--qr-reveal-step` (10ms unless set). Finders and
+    /// overlays stay static; the animation itself is left to the embedding CSS.
+    pub reveal_order: Option<RevealOrder>,
+    
+    /// Annotate each data module and finder element with `data-x` and `data-y`
+    /// attributes holding its module coordinates (of the top-left module for larger
+    /// elements), for inspecting a render in browser devtools. Off by default.
+    pub debug_attrs: bool,
+    
+    /// `id` attribute of the root `<svg>` element. Also prefixes the ids of the
+    /// internal definitions (gradients, the ring path), so several codes can be
+    /// inlined in one document without their ids colliding.
+    pub svg_id: Option<String>,
+    /// `class` attribute of the root `<svg>` element
+    pub svg_class: Option<String>,
+    /// Rotate the SVG drawing within its viewBox; the module data is unchanged
+    pub rotation: Rotation,
 }
 
 impl Default for FancyOptions {
@@ -61,19 +465,337 @@ impl Default for FancyOptions {
             color_background: "#FFFFFF".to_string(),
             color_data: "#000000".to_string(),
             color_finder: "#000000".to_string(),
+            gradient_data: None,
+            gradient_finder: None,
             shape_module: ModuleShape::Square,
+            module_shape_fn: None,
+            corner_radius_map: None,
             shape_finder: FinderShape::Square,
+            finder_ball_shape: None,
+            finder_inner_padding: 1.0,
+            finder_ball_scale: 1.0,
+            finder_rings: None,
+            custom_finders: true,
             center_image_url: None,
             center_text: None,
-            overlay_scale: 0.2,
+            overlay_scale: OverlayScale::default(),
+            overlay_shape: OverlayShape::Square,
+            overlay_glass: None,
+            ring_data_color: None,
+            palette: None,
+            background_dots: None,
+            ring_text: None,
+            quiet_zone_decoration: None,
+            solid_timing: false,
+            pixel_snap: None,
+            reveal_order: None,
+            debug_attrs: false,
+            svg_id: None,
+            svg_class: None,
+            rotation: Rotation::None,
+        }
+    }
+}
+
+impl FancyOptions {
+    /// Returns the options of the given preset.
+    /// 
+    /// `overlay_scale` is the theme's logo size, or `OverlayScale::NONE` for themes
+    /// without a logo area. No center image or text is set.
+    pub fn from_theme(theme: Theme) -> Self {
+        // (background, data, finder, module shape, finder shape, logo scale)
+        let (bg, data, finder, shape_module, shape_finder, scale) = match theme {
+            Theme::Standard        => ("#FFFFFF", "#4d3695", "#4d3695", ModuleShape::RoundedSquare(0.3), FinderShape::Rounded(1.5), 0.3),
+            Theme::MinimalLogo     => ("#FFFFFF", "#000000", "#4d3695", ModuleShape::Square, FinderShape::Rounded(1.0), 0.25),
+            Theme::GradientLogo    => ("#F5F3FF", "#4d3695", "#5B34A8", ModuleShape::Circle, FinderShape::Rounded(2.0), 0.28),
+            Theme::Premium         => ("#FFFFFF", "#4d3695", "#4d3695", ModuleShape::RoundedSquare(0.35), FinderShape::Rounded(1.8), 0.26),
+            Theme::BrandedFinders  => ("#FFFFFF", "#1a1a1a", "#4d3695", ModuleShape::RoundedSquare(0.25), FinderShape::Rounded(2.2), 0.0),
+            Theme::MinimalFinders  => ("#FFFFFF", "#000000", "#4d3695", ModuleShape::Square, FinderShape::Rounded(1.5), 0.0),
+            Theme::GradientFinders => ("#FAF5FF", "#6B4B8A", "#4d3695", ModuleShape::Circle, FinderShape::Rounded(2.5), 0.0),
+            Theme::GradientMinimal => ("#FAF5FF", "#6B4B8A", "#4d3695", ModuleShape::Square, FinderShape::Rounded(1.5), 0.25),
+        };
+        FancyOptions {
+            color_background: bg.to_string(),
+            color_data: data.to_string(),
+            color_finder: finder.to_string(),
+            shape_module,
+            shape_finder,
+            overlay_scale: OverlayScale::clamped(scale),
+            ..FancyOptions::default()
+        }
+    }
+    
+    /// Returns a high-contrast configuration for accessibility-first deployments:
+    /// black square modules and finders on white, solid timing patterns, and no
+    /// gradients, overlay, ring text or quiet zone decoration.
+    /// 
+    /// Rendered from a `FancyQr` with the standard 4-module quiet zone (the default),
+    /// it passes every `validate()` check, so it is a safe fallback style.
+    pub fn accessible() -> Self {
+        FancyOptions {
+            color_background: "#FFFFFF".to_string(),
+            color_data: "#000000".to_string(),
+            color_finder: "#000000".to_string(),
+            shape_module: ModuleShape::Square,
+            shape_finder: FinderShape::Square,
+            custom_finders: false,
+            overlay_scale: OverlayScale::NONE,
+            solid_timing: true,
+            ..FancyOptions::default()
+        }
+    }
+    
+    /// Returns the lowest ECC level these options should be rendered with.
+    /// 
+    /// This is `Low` without a center overlay. With one, it is at least `Quartile`,
+    /// and `High` if at some version the overlay could hide more modules than Quartile's
+    /// error correction budget, measured as in `FancyQr::validate`. So a code encoded at
+    /// this level or higher gets no `OverlayTooLarge` or `LowEccForOverlay` warning,
+    /// unless the overlay is too large even for `High`.
+    pub fn minimum_ecc(&self) -> QrCodeEcc {
+        // Every module of the safe zone is counted as hidden, an upper bound for any code
+        Version::all()
+            .map(|ver| {
+                let width = ver.module_count() as usize;
+                let span = (0..width).filter(|&i| FancyQr::is_safe_zone(i, i, width, self)).count();
+                FancyQr::overlay_minimum_ecc(self, ver, span * span)
+            })
+            .max()
+            .unwrap_or(QrCodeEcc::High)
+    }
+    
+    /// Returns these options with the given colors. An empty string keeps the current color,
+    /// so e.g. only the data color can be overridden.
+    pub fn with_colors(mut self, background: &str, data: &str, finder: &str) -> Self {
+        if !background.is_empty() {
+            self.color_background = background.to_string();
+        }
+        if !data.is_empty() {
+            self.color_data = data.to_string();
+        }
+        if !finder.is_empty() {
+            self.color_finder = finder.to_string();
+        }
+        self
+    }
+    
+    /// Parses a compact style spec of comma-separated `key=value` pairs, starting
+    /// from the default options, e.g. `"data=#123456,finder=#abcdef,module=circle,overlay=0.2"`.
+    /// 
+    /// Keys are `background`, `data` and `finder` (hex colors), `module` (`square`,
+    /// `circle`, `rounded`, `vbars`, `hbars` or `squircle`), `finder_shape` (`square`, `rounded`
+    /// or `dot`) and `overlay` (0.0 to 0.3). Later pairs override earlier ones.
+    pub fn parse(spec: &str) -> Result<Self, ParseStyleError> {
+        let mut options = FancyOptions::default();
+        for item in spec.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let (key, value) = item.split_once('=')
+                .ok_or_else(|| ParseStyleError::Syntax(item.to_string()))?;
+            let (key, value) = (key.trim(), value.trim());
+            let color = || match parse_hex_color(value) {
+                Some(_) => Ok(value.to_string()),
+                None => Err(ParseStyleError::InvalidColor(value.to_string())),
+            };
+            match key {
+                "background" => options.color_background = color()?,
+                "data" => options.color_data = color()?,
+                "finder" => options.color_finder = color()?,
+                "module" => options.shape_module = match value.to_ascii_lowercase().as_str() {
+                    "square" => ModuleShape::Square,
+                    "circle" => ModuleShape::Circle,
+                    "rounded" => ModuleShape::RoundedSquare(0.3),
+                    "vbars" => ModuleShape::VerticalBars { radius: 0.5 },
+                    "hbars" => ModuleShape::HorizontalBars { radius: 0.5 },
+                    "squircle" => ModuleShape::Squircle { n: 4.0 },
+                    _ => return Err(ParseStyleError::UnknownShape(value.to_string())),
+                },
+                "finder_shape" => options.shape_finder = match value.to_ascii_lowercase().as_str() {
+                    "square" => FinderShape::Square,
+                    "rounded" => FinderShape::Rounded(1.5),
+                    "dot" => FinderShape::Dot,
+                    _ => return Err(ParseStyleError::UnknownShape(value.to_string())),
+                },
+                "overlay" => options.overlay_scale = value.parse::<f32>().ok()
+                    .and_then(|scale| OverlayScale::new(scale).ok())
+                    .ok_or_else(|| ParseStyleError::OutOfRange(value.to_string()))?,
+                _ => return Err(ParseStyleError::UnknownKey(key.to_string())),
+            }
+        }
+        Ok(options)
+    }
+}
+
+impl TryFrom<&str> for FancyOptions {
+    type Error = ParseStyleError;
+    
+    /// Parses a style spec; see `FancyOptions::parse`.
+    fn try_from(spec: &str) -> Result<Self, Self::Error> {
+        Self::parse(spec)
+    }
+}
+
+/// A heuristic estimate of how reliably a styled code will scan.
+/// 
+/// Each factor is scored from 0 (will not scan) to 100 (no concern), and `overall`
+/// combines them multiplicatively so a single failing factor drags the total down.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ScanScore {
+    /// The combined estimate, from 0 to 100.
+    pub overall: u8,
+    /// Contrast between the module colors and the background.
+    pub contrast: u8,
+    /// How much of the error correction budget the center overlay consumes.
+    pub overlay: u8,
+    /// Width of the quiet zone relative to the 4 modules the standard requires.
+    pub quiet_zone: u8,
+    /// How much of each module cell the chosen shape leaves empty.
+    pub module_gap: u8,
+}
+
+/// A potential scanning problem detected while rendering.
+/// 
+/// Warnings never stop rendering; they let callers surface issues to users.
+#[derive(Clone, PartialEq, Debug)]
+pub enum RenderWarning {
+    /// The overlay hides a larger fraction of the data modules than error correction can
+    /// safely recover (covered fraction, recoverable fraction). See `QrCode::max_erasable_modules`.
+    OverlayTooLarge(f32, f32),
+    /// A module color has too little contrast against the background (color, contrast ratio).
+    LowContrast(String, f32),
+    /// The quiet zone is narrower than the 4 modules the standard requires (actual width).
+    SmallQuietZone(usize),
+    /// A color is not a "#RGB"/"#RRGGBB" hex value, so its contrast could not be checked.
+    UnvalidatedColor(String),
+    /// The overlay hides modules of finder, alignment, timing or format patterns, which
+    /// error correction cannot restore (number of function modules covered).
+    OverlayCoversFunctionPattern(usize),
+    /// A center overlay is configured but the code uses a lower ECC level than
+    /// overlays need (actual level, minimum level). See `FancyOptions::minimum_ecc`.
+    LowEccForOverlay(QrCodeEcc, QrCodeEcc),
+    /// A module color is the same as the background, so those modules are invisible (color).
+    /// Reported instead of `LowContrast` for that color.
+    SameAsBackground(String),
+}
+
+impl std::fmt::Display for RenderWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::OverlayTooLarge(covered, recoverable) =>
+                write!(f, "Overlay covers {:.0}% of the code but only {:.0}% can be recovered", covered * 100.0, recoverable * 100.0),
+            Self::LowContrast(color, ratio) =>
+                write!(f, "Color {} has low contrast against the background ({:.1}:1)", color, ratio),
+            Self::SmallQuietZone(width) =>
+                write!(f, "Quiet zone of {} modules is smaller than the required 4", width),
+            Self::UnvalidatedColor(color) =>
+                write!(f, "Color {} is not a hex value and could not be checked", color),
+            Self::OverlayCoversFunctionPattern(count) =>
+                write!(f, "Overlay covers {} modules of finder, alignment or timing patterns", count),
+            Self::LowEccForOverlay(actual, minimum) =>
+                write!(f, "Error correction {} is too low for a center overlay (use at least {})", actual, minimum),
+            Self::SameAsBackground(color) =>
+                write!(f, "Color {} is the same as the background, so the code is invisible", color),
         }
     }
 }
 
+/// The structural role of a module position, as returned by `FancyQr::module_role`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ModuleRole {
+    /// A data or error correction module
+    Data,
+    /// Part of a finder pattern or its light separator
+    Finder,
+    /// Part of a timing pattern
+    Timing,
+    /// Part of an alignment pattern
+    Alignment,
+    /// Format or version information, including the always-dark module
+    Format,
+    /// In the quiet zone around the code
+    Quiet,
+}
+
+// The minimum contrast ratio between module and background colors considered scannable.
+const MIN_CONTRAST_RATIO: f32 = 3.0;
+
+// The share of `QrCode::max_erasable_modules` an overlay may hide, leaving the rest for
+// codewords the overlay only partly covers and for ordinary print or camera damage.
+const OVERLAY_BUDGET_FRACTION: f32 = 0.5;
+
 /// A fancy QR code with customizable rendering options.
+/// 
+/// The code is encoded once, when the `FancyQr` is created; rendering only reads it.
+/// Keep (or clone) a `FancyQr` to re-render it with different options, e.g. in a live
+/// preview where only colors change. Layout information derived from the code is
+/// computed on first use and shared by all later renders.
 pub struct FancyQr {
     code: QrCode,
     quiet_zone: usize,
+    // Function module map of the code (indexed r * width + c), computed on first use
+    function: OnceLock<Vec<bool>>,
+    // Finder boxes of the last rendered finder style, reused while only colors change
+    finders: Mutex<Option<(FinderStyle, Arc<Vec<FinderBox>>)>>,
+}
+
+impl Clone for FancyQr {
+    fn clone(&self) -> Self {
+        FancyQr {
+            code: self.code.clone(),
+            quiet_zone: self.quiet_zone,
+            function: self.function.clone(),
+            finders: Mutex::new(self.finders.lock().unwrap_or_else(|e| e.into_inner()).clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    // Counts function module map computations, to test that renders reuse the cached map
+    static FUNCTION_MAP_COMPUTATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    // Counts finder geometry computations, to test that color changes reuse the boxes
+    static FINDER_GEOMETRY_COMPUTATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+// The options that determine the finder geometry (but not its colors)
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct FinderStyle {
+    shape: FinderShape,
+    ball_shape: Option<FinderShape>,
+    inner_padding: f32,
+    ball_scale: f32,
+    rings: usize,
+}
+
+impl FinderStyle {
+    fn of(options: &FancyOptions) -> Self {
+        FinderStyle {
+            shape: options.shape_finder,
+            ball_shape: options.finder_ball_shape,
+            inner_padding: options.finder_inner_padding,
+            ball_scale: options.finder_ball_scale,
+            rings: options.finder_rings.as_ref().map_or(0, |rings| rings.len()),
+        }
+    }
+}
+
+// Which part of a finder pattern a box draws, which decides its fill
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum FinderLayer {
+    Frame,
+    Cutout,
+    Ball,
+    // The ring at this index of `finder_rings`
+    Ring(usize),
+}
+
+// One square-footprint element of a finder pattern, in matrix coordinates (without the quiet zone)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct FinderBox {
+    pub(crate) layer: FinderLayer,
+    pub(crate) shape: FinderShape,
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) size: f32,
+    pub(crate) rx: f32,
 }
 
 impl FancyQr {
@@ -83,200 +805,847 @@ impl FancyQr {
     /// which is important when using center overlays.
     pub fn from_text(text: &str) -> Result<Self, DataTooLong> {
         let code = QrCode::encode_text(text, QrCodeEcc::High)?;
-        Ok(FancyQr { 
-            code,
-            quiet_zone: 4, // Standard white border width
-        })
+        Ok(FancyQr::from_qrcode(code))
     }
     
     /// Creates a new fancy QR code from binary data with High Error Correction.
     pub fn from_binary(data: &[u8]) -> Result<Self, DataTooLong> {
         let code = QrCode::encode_binary(data, QrCodeEcc::High)?;
-        Ok(FancyQr { 
-            code,
-            quiet_zone: 4,
-        })
+        Ok(FancyQr::from_qrcode(code))
     }
     
     /// Creates a fancy QR code from text with a specific error correction level.
     pub fn from_text_with_ecc(text: &str, ecl: QrCodeEcc) -> Result<Self, DataTooLong> {
         let code = QrCode::encode_text(text, ecl)?;
-        Ok(FancyQr { 
-            code,
-            quiet_zone: 4,
-        })
+        Ok(FancyQr::from_qrcode(code))
+    }
+    
+    /// Creates a fancy QR code from text for rendering with the given options, raising
+    /// the ECC level to `options.minimum_ecc()` if the requested level is lower.
+    /// 
+    /// Use this instead of `from_text_with_ecc` when a center overlay may be configured,
+    /// since an overlay on a Low or Medium code frequently makes it unscannable.
+    pub fn from_text_with_options(text: &str, ecl: QrCodeEcc, options: &FancyOptions) -> Result<Self, DataTooLong> {
+        FancyQr::from_text_with_ecc(text, ecl.max(options.minimum_ecc()))
+    }
+    
+    /// Creates a fancy QR code from a list of segments, as `QrCode::encode_segments()` does.
+    /// 
+    /// This allows styling hand-optimized mixed-mode codes (e.g. numeric + byte).
+    /// Use High error correction when adding overlays.
+    pub fn from_segments(segs: &[QrSegment], ecl: QrCodeEcc) -> Result<Self, DataTooLong> {
+        let code = QrCode::encode_segments(segs, ecl)?;
+        Ok(FancyQr::from_qrcode(code))
+    }
+    
+    /// Returns this code re-encoded at another ECC level, keeping its content and quiet zone.
+    /// 
+    /// The content is recovered from the stored code (see `QrCode::segments()`), so the
+    /// original text is not needed. As with `from_segments`, the smallest version is
+    /// chosen and the ECC level may be boosted if that fits in the same version.
+    pub fn reencode_with_ecc(&self, ecl: QrCodeEcc) -> Result<Self, DataTooLong> {
+        let code = QrCode::encode_segments(&self.code.segments(), ecl)?;
+        Ok(FancyQr::from_qrcode(code).with_quiet_zone(self.quiet_zone))
     }
     
     /// Creates a fancy QR code from an existing QrCode.
     pub fn from_qrcode(code: QrCode) -> Self {
         FancyQr { 
             code,
-            quiet_zone: 4,
+            quiet_zone: 4, // Standard white border width
+            function: OnceLock::new(),
+            finders: Mutex::new(None),
         }
     }
     
     /// Sets the quiet zone (white border) size in modules.
+    /// 
+    /// The QR Code specification requires a quiet zone of at least 4 modules.
     pub fn with_quiet_zone(mut self, size: usize) -> Self {
         self.quiet_zone = size;
         self
     }
     
+    /// Removes the quiet zone so the rendered SVG contains only the modules.
+    /// 
+    /// The viewBox then equals the matrix width and the top-left module sits at (0, 0).
+    /// Borderless codes are not spec-compliant and may not scan reliably unless the
+    /// surrounding layout supplies its own light margin.
+    pub fn borderless(self) -> Self {
+        self.with_quiet_zone(0)
+    }
+    
+    /// Returns the quiet zone (white border) size in modules.
+    pub fn quiet_zone(&self) -> usize {
+        self.quiet_zone
+    }
+    
     /// Returns a reference to the underlying QR code.
     pub fn qrcode(&self) -> &QrCode {
         &self.code
     }
+    
+    /// Returns the role of the module at the given position, for custom renderers
+    /// that style each kind of module differently.
+    /// 
+    /// Coordinates include the quiet zone, as in the rendered SVG: (0, 0) is the
+    /// top-left corner of the border. Positions outside the matrix are `Quiet`.
+    pub fn module_role(&self, x: usize, y: usize) -> ModuleRole {
+        let width = self.code.size() as usize;
+        let (c, r) = match (x.checked_sub(self.quiet_zone), y.checked_sub(self.quiet_zone)) {
+            (Some(c), Some(r)) if c < width && r < width => (c, r),
+            _ => return ModuleRole::Quiet,
+        };
+        if !self.function_modules()[r * width + c] {
+            return ModuleRole::Data;
+        }
+        if (r < 8 && (c < 8 || c >= width - 8)) || (r >= width - 8 && c < 8) {
+            ModuleRole::Finder
+        } else if self.is_alignment_module(c, r) {
+            ModuleRole::Alignment
+        } else if r == 6 || c == 6 {
+            ModuleRole::Timing
+        } else {
+            ModuleRole::Format
+        }
+    }
+
+    // Tests whether the module at (column, row) of the matrix lies in one of the drawn
+    // alignment patterns. The three grid positions that overlap the finders are skipped.
+    pub(crate) fn is_alignment_module(&self, c: usize, r: usize) -> bool {
+        let positions = self.code.get_alignment_pattern_positions();
+        let last = positions.len().saturating_sub(1);
+        let near = |a: usize, b: i32| a.abs_diff(b as usize) <= 2;
+        positions.iter().enumerate().any(|(i, &py)| {
+            positions.iter().enumerate().any(|(j, &px)| {
+                let overlaps_finder = (i == 0 && (j == 0 || j == last)) || (i == last && j == 0);
+                !overlaps_finder && near(r, py) && near(c, px)
+            })
+        })
+    }
 
     /// Renders the QR code to a standalone SVG string with custom styling.
+    /// 
+    /// The output is deterministic: the same code and options give a byte-identical
+    /// document, with every coordinate printed with at most 3 decimals.
     pub fn render_svg(&self, options: &FancyOptions) -> String {
         let matrix_width = self.code.size() as usize;
         let full_width = matrix_width + (self.quiet_zone * 2);
         
+        let ring_pad = self.ring_padding(options);
+        let unit = options.pixel_snap.map_or(1, NonZeroU32::get);
+        let canvas_origin = -(ring_pad as i64) * i64::from(unit);
+        let canvas_width = (full_width + ring_pad * 2) * unit as usize;
+        let px = |v: f32| snap_px(v, options.pixel_snap);
+        let len = |v: f32| snap_len(v, options.pixel_snap);
+        // Internal definition ids, namespaced by the root id
+        let def_id = |name: &str| match &options.svg_id {
+            Some(id) => crate::render::escape_xml(&format!("{}-{}", id, name)),
+            None => name.to_string(),
+        };
+        
         // SVG Header
         let mut svg = String::new();
         svg.push_str(&format!(
-            r#"<svg viewBox="0 0 {w} {w}" xmlns="http://www.w3.org/2000/svg" shape-rendering="geometricPrecision">"#,
-            w = full_width
+            r#"<svg{a} viewBox="{o} {o} {w} {w}" xmlns="http://www.w3.org/2000/svg" shape-rendering="geometricPrecision">"#,
+            a = crate::render::root_attributes(options.svg_id.as_deref(), options.svg_class.as_deref()),
+            o = canvas_origin, w = canvas_width
         ));
+        let rotation = crate::render::rotation_group(options.rotation, canvas_origin as f32, canvas_width as f32);
+        if let Some(group) = &rotation {
+            svg.push_str(group);
+        }
 
         // 1. Background Layer
         svg.push_str(&format!(
-            r#"<rect x="0" y="0" width="{w}" height="{w}" fill="{c}" />"#,
-            w = full_width, c = options.color_background
+            r#"<rect x="{o}" y="{o}" width="{w}" height="{w}" fill="{c}" />"#,
+            o = canvas_origin, w = canvas_width, c = options.color_background
         ));
+        
+        // Quiet zone decoration, confined to the outermost module ring
+        match options.quiet_zone_decoration.as_ref().filter(|_| self.quiet_zone > 0) {
+            Some(QuietZoneDecoration::InnerLine { color, width }) => {
+                let w = width.clamp(0.0, 1.0);
+                svg.push_str(&format!(
+                    r#"<rect x="{o}" y="{o}" width="{s}" height="{s}" rx="{rx}" fill="none" stroke="{color}" stroke-width="{sw}" />"#,
+                    o = px(w / 2.0), s = snap_span(w / 2.0, full_width as f32 - w / 2.0, options.pixel_snap), rx = len(1.0), sw = len(w)
+                ));
+            },
+            Some(QuietZoneDecoration::Dots { color }) => {
+                svg.push_str(&format!(r#"<g fill="{}">"#, color));
+                let last = full_width - 1;
+                for i in 0..last {
+                    // Clockwise around the ring, one dot per module
+                    for (c, r) in [(i, 0), (last, i), (last - i, last), (0, last - i)] {
+                        svg.push_str(&format!(
+                            r#"<circle cx="{cx}" cy="{cy}" r="{r}" />"#,
+                            cx = px(c as f32 + 0.5), cy = px(r as f32 + 0.5), r = len(0.15)
+                        ));
+                    }
+                }
+                svg.push_str("</g>");
+            },
+            None => {},
+        }
+        
+        // Gradient definitions (a gradient shared by data and finders is defined once)
+        let data_fill = match &options.gradient_data {
+            Some(_) => format!("url(#{})", def_id("qr-gradient-data")),
+            None => options.color_data.clone(),
+        };
+        let finder_fill = match &options.gradient_finder {
+            Some(g) if options.gradient_data.as_ref() == Some(g) => data_fill.clone(),
+            Some(_) => format!("url(#{})", def_id("qr-gradient-finder")),
+            None => options.color_finder.clone(),
+        };
+        let clip_logo = options.center_image_url.is_some() && options.overlay_shape == OverlayShape::Circle;
+        if options.gradient_data.is_some() || options.gradient_finder.is_some() || options.ring_text.is_some() || clip_logo {
+            svg.push_str("<defs>");
+            if let Some(g) = &options.gradient_data {
+                Self::push_gradient_def(&mut svg, &def_id("qr-gradient-data"), g, self.quiet_zone, matrix_width, options.pixel_snap);
+            }
+            if let Some(g) = options.gradient_finder.as_ref().filter(|_| finder_fill != data_fill) {
+                Self::push_gradient_def(&mut svg, &def_id("qr-gradient-finder"), g, self.quiet_zone, matrix_width, options.pixel_snap);
+            }
+            if let Some(ring) = &options.ring_text {
+                // Clockwise circle starting at the left, so text reads upright across the top
+                let c = full_width as f32 / 2.0;
+                let r = ring.effective_radius(matrix_width);
+                svg.push_str(&format!(
+                    r#"<path id="{id}" d="M {x},{c} a {r},{r} 0 1,1 {d},0 a {r},{r} 0 1,1 {nd},0" fill="none" />"#,
+                    id = def_id("qr-ring-path"),
+                    x = px(c - r), c = px(c), r = len(r), d = snap_span(c - r, c + r, options.pixel_snap), nd = snap_span(c + r, c - r, options.pixel_snap)
+                ));
+            }
+            if clip_logo {
+                let c = full_width as f32 / 2.0;
+                let r = matrix_width as f32 * options.overlay_scale.value() / 2.0;
+                svg.push_str(&format!(
+                    r#"<clipPath id="{id}"><circle cx="{c}" cy="{c}" r="{r}" /></clipPath>"#,
+                    id = def_id("qr-logo-clip"), c = px(c), r = len(r)
+                ));
+            }
+            svg.push_str("</defs>");
+        }
 
         // Calculate Safe Zone (Center)
         let center_idx = matrix_width as f32 / 2.0;
-        let safe_size = matrix_width as f32 * options.overlay_scale;
-        let safe_min = center_idx - (safe_size / 2.0);
-        let safe_max = center_idx + (safe_size / 2.0);
-
-        let is_safe_zone = |c: usize, r: usize| -> bool {
-            if options.center_image_url.is_none() && options.center_text.is_none() {
-                return false;
-            }
-            let fx = c as f32;
-            let fy = r as f32;
-            fx >= safe_min && fx <= safe_max && fy >= safe_min && fy <= safe_max
-        };
+        let safe_size = matrix_width as f32 * options.overlay_scale.value();
 
-        // 2. Render Data Modules
+        // 2. Render Data Modules (grouped under a single fill)
+        let elements = self.data_elements(options);
+        let reveal = options.reveal_order.map(|order| {
+            Self::reveal_ranks(matrix_width, order, |c, r| elements[r * matrix_width + c].is_some())
+        });
+        svg.push_str(&format!(r#"<g fill="{}">"#, data_fill));
         for r in 0..matrix_width {
             for c in 0..matrix_width {
-                // Skip light modules
-                if !self.code.get_module(c as i32, r as i32) { 
-                    continue; 
-                }
-                
-                // Identify Finders (7x7 corners)
-                let is_finder = Self::is_finder_module(c, r, matrix_width);
+                // Skip light modules, raw finders, the center safety zone and the inside of bar runs
+                let Some((shape, run)) = elements[r * matrix_width + c] else {
+                    continue;
+                };
 
-                // Skip rendering raw finders (we draw custom ones later)
-                if is_finder { 
-                    continue; 
+                // Draw Module
+                let x = px((c + self.quiet_zone) as f32);
+                let y = px((r + self.quiet_zone) as f32);
+                let one = len(1.0);
+                let mut a = match &reveal {
+                    Some(ranks) => format!(
+                        r#" data-reveal="{i}" style="animation-delay:calc({i} * var(--qr-reveal-step, 10ms))""#,
+                        i = ranks[r * matrix_width + c]
+                    ),
+                    None => String::new(),
+                };
+                if options.debug_attrs {
+                    a.push_str(&debug_attrs(c, r));
                 }
-                
-                // Skip rendering center safety zone
-                if is_safe_zone(c, r) { 
-                    continue; 
+                let color = options.ring_data_color.as_deref()
+                    .filter(|_| Self::is_safe_zone_frame(c, r, matrix_width, options))
+                    .or_else(|| palette_color(options, c, r, matrix_width));
+                if let Some(color) = color {
+                    a.push_str(&format!(r#" fill="{}""#, color));
                 }
 
-                // Draw Module
-                let x = c + self.quiet_zone;
-                let y = r + self.quiet_zone;
-                let fill = &options.color_data;
-
-                match options.shape_module {
+                match shape {
                     ModuleShape::Square => {
-                        svg.push_str(&format!(r#"<rect x="{x}" y="{y}" width="1" height="1" fill="{fill}" />"#));
+                        svg.push_str(&format!(r#"<rect x="{x}" y="{y}" width="{one}" height="{one}"{a} />"#));
                     },
                     ModuleShape::Circle => {
                         svg.push_str(&format!(
-                            r#"<circle cx="{cx}" cy="{cy}" r="0.45" fill="{fill}" />"#, 
-                            cx=x as f32 + 0.5, 
-                            cy=y as f32 + 0.5
+                            r#"<circle cx="{cx}" cy="{cy}" r="{r}"{a} />"#, 
+                            cx=px((c + self.quiet_zone) as f32 + 0.5), 
+                            cy=px((r + self.quiet_zone) as f32 + 0.5),
+                            r=len(0.45)
                         ));
                     },
                     ModuleShape::RoundedSquare(rad) => {
                         svg.push_str(&format!(
-                            r#"<rect x="{x}" y="{y}" width="1" height="1" rx="{rad}" fill="{fill}" />"#
+                            r#"<rect x="{x}" y="{y}" width="{one}" height="{one}" rx="{rx}"{a} />"#,
+                            rx=len(rad)
                         ));
-                    }
+                    },
+                    ModuleShape::VerticalBars { radius } => {
+                        svg.push_str(&format!(
+                            r#"<rect x="{x}" y="{y}" width="{one}" height="{h}" rx="{rx}"{a} />"#,
+                            h=len(run as f32), rx=len(radius)
+                        ));
+                    },
+                    ModuleShape::HorizontalBars { radius } => {
+                        svg.push_str(&format!(
+                            r#"<rect x="{x}" y="{y}" width="{w}" height="{one}" rx="{rx}"{a} />"#,
+                            w=len(run as f32), rx=len(radius)
+                        ));
+                    },
+                    ModuleShape::Squircle { n } => {
+                        // Four quadrant curves from the right middle, clockwise in SVG coordinates
+                        let cx = (c + self.quiet_zone) as f32 + 0.5;
+                        let cy = (r + self.quiet_zone) as f32 + 0.5;
+                        let d = squircle_handle(n) * 0.5;
+                        svg.push_str(&format!(
+                            r#"<path d="M{r},{cy} C{r},{yd} {xd},{b} {cx},{b} C{xnd},{b} {l},{yd} {l},{cy} C{l},{ynd} {xnd},{t} {cx},{t} C{xd},{t} {r},{ynd} {r},{cy}Z"{a} />"#,
+                            r=px(cx + 0.5), l=px(cx - 0.5), t=px(cy - 0.5), b=px(cy + 0.5),
+                            cx=px(cx), cy=px(cy), xd=px(cx + d), xnd=px(cx - d), yd=px(cy + d), ynd=px(cy - d)
+                        ));
+                    },
                 }
             }
         }
+        svg.push_str("</g>");
+        
+        if let Some((color, radius)) = &options.background_dots {
+            svg.push_str(&format!(r#"<g fill="{}">"#, color));
+            for (c, r) in self.background_dot_cells(options) {
+                svg.push_str(&format!(
+                    r#"<circle cx="{cx}" cy="{cy}" r="{r}" />"#,
+                    cx = px((c + self.quiet_zone) as f32 + 0.5),
+                    cy = px((r + self.quiet_zone) as f32 + 0.5),
+                    r = len(radius.clamp(0.0, 0.5))
+                ));
+            }
+            svg.push_str("</g>");
+        }
 
         // 3. Render Custom Finder Patterns
-        Self::render_finder_patterns(&mut svg, matrix_width, self.quiet_zone, options);
+        if options.custom_finders {
+            self.render_finder_patterns(&mut svg, options, &finder_fill);
+        }
 
         // 4. Render Center Overlay
-        Self::render_center_overlay(&mut svg, center_idx, safe_size, self.quiet_zone, options);
+        let logo_clip = clip_logo.then(|| def_id("qr-logo-clip"));
+        Self::render_center_overlay(&mut svg, center_idx, safe_size, self.quiet_zone, options, logo_clip.as_deref());
 
+        // 5. Render Ring Caption
+        if let Some(ring) = &options.ring_text {
+            svg.push_str(&format!(
+                r##"<text font-family="{font}" font-weight="bold" font-size="{sz}" text-anchor="middle" fill="{fg}"><textPath href="#{id}" startOffset="25%">{txt}</textPath></text>"##,
+                id = def_id("qr-ring-path"),
+                font = crate::render::escape_xml(&ring.font),
+                sz = len(RingText::font_size(ring.effective_radius(matrix_width))),
+                fg = ring.color,
+                txt = crate::render::escape_xml(&ring.text)
+            ));
+        }
+
+        if rotation.is_some() {
+            svg.push_str("</g>");
+        }
         svg.push_str("</svg>");
         svg
     }
     
+    /// Renders the QR code to SVG and also returns any detected scanning problems.
+    /// 
+    /// The SVG is always produced, exactly as `render_svg` would; the warnings are
+    /// the same ones returned by `validate`.
+    pub fn render_svg_checked(&self, options: &FancyOptions) -> (String, Vec<RenderWarning>) {
+        (self.render_svg(options), self.validate(options))
+    }
+    
+    /// Checks the given options against this code for likely scanning problems.
+    pub fn validate(&self, options: &FancyOptions) -> Vec<RenderWarning> {
+        let mut warnings = Vec::new();
+        
+        let has_overlay = options.center_image_url.is_some() || options.center_text.is_some();
+        if has_overlay {
+            let (covered, recoverable) = self.overlay_coverage(options);
+            if covered > recoverable {
+                warnings.push(RenderWarning::OverlayTooLarge(covered, recoverable));
+            }
+            let minimum = Self::overlay_minimum_ecc(options, self.code.version(), self.overlay_hidden_modules(options));
+            if self.code.error_correction_level() < minimum {
+                warnings.push(RenderWarning::LowEccForOverlay(self.code.error_correction_level(), minimum));
+            }
+            
+            // Function patterns are not protected by error correction at all
+            let width = self.code.size() as usize;
+            let function = self.function_modules();
+            let hidden = (0..width * width)
+                .filter(|&i| function[i] && Self::is_safe_zone(i % width, i / width, width, options))
+                .count();
+            if hidden > 0 {
+                warnings.push(RenderWarning::OverlayCoversFunctionPattern(hidden));
+            }
+        }
+        
+        let background = parse_hex_color(&options.color_background);
+        if background.is_none() {
+            warnings.push(RenderWarning::UnvalidatedColor(options.color_background.clone()));
+        }
+        let palette = options.palette.iter().flat_map(|(colors, _)| colors);
+        for color in [&options.color_data, &options.color_finder].into_iter().chain(palette) {
+            // Also catches equal named colors, which the contrast check cannot parse
+            let same = match (parse_hex_color(color), background) {
+                (Some(fg), Some(bg)) => fg == bg,
+                _ => color.trim().eq_ignore_ascii_case(options.color_background.trim()),
+            };
+            if same {
+                warnings.push(RenderWarning::SameAsBackground(color.clone()));
+                continue;
+            }
+            match (parse_hex_color(color), background) {
+                (None, _) => warnings.push(RenderWarning::UnvalidatedColor(color.clone())),
+                (Some(fg), Some(bg)) => {
+                    let ratio = contrast_ratio(fg, bg);
+                    if ratio < MIN_CONTRAST_RATIO {
+                        warnings.push(RenderWarning::LowContrast(color.clone(), ratio));
+                    }
+                },
+                (Some(_), None) => {},
+            }
+        }
+        
+        // A decoration uses the outermost ring of the quiet zone
+        let clear_width = match options.quiet_zone_decoration {
+            Some(_) => self.quiet_zone.saturating_sub(1),
+            None => self.quiet_zone,
+        };
+        if clear_width < 4 {
+            warnings.push(RenderWarning::SmallQuietZone(clear_width));
+        }
+        warnings
+    }
+    
+    /// Renders the QR code to SVG (see `render_svg`) wrapped in a base64
+    /// `data:image/svg+xml` URI, ready for an `<img src>`.
+    pub fn to_data_uri(&self, options: &FancyOptions) -> String {
+        crate::render::svg_data_uri(&self.render_svg(options))
+    }
+    
     /// Renders the QR code to SVG with default options.
     pub fn render_svg_default(&self) -> String {
         self.render_svg(&FancyOptions::default())
     }
     
-    // Helper: Check if a module is part of a finder pattern
-    fn is_finder_module(c: usize, r: usize, width: usize) -> bool {
-        // Top-Left (0,0), Top-Right (W-7, 0), Bottom-Left (0, W-7)
-        (r < 7 && c < 7) || 
-        (r < 7 && c >= width.saturating_sub(7)) || 
-        (r >= width.saturating_sub(7) && c < 7)
-    }
+    /// Estimates how reliably this code will scan when rendered with the given options.
+    /// 
+    /// The score combines color contrast, the overlay size relative to the error
+    /// correction level, the quiet zone width, and the gaps left by the module shape.
+    /// It is a heuristic to steer users away from broken styles, not a guarantee.
+    pub fn scan_reliability(&self, options: &FancyOptions) -> ScanScore {
+        // Contrast: WCAG ratio of 7:1 or better is ideal, 1:1 is invisible.
+        // Unparseable colors get a neutral score since they can't be checked.
+        let contrast_of = |fg: &str| match (parse_hex_color(fg), parse_hex_color(&options.color_background)) {
+            (Some(fg), Some(bg)) => ((contrast_ratio(fg, bg) - 1.0) / 6.0).clamp(0.0, 1.0),
+            _ => 0.5,
+        };
+        let contrast = contrast_of(&options.color_data).min(contrast_of(&options.color_finder));
+        
+        // Overlay: fraction of the data modules covered versus what the ECC blocks can recover
+        let has_overlay = options.center_image_url.is_some() || options.center_text.is_some();
+        let overlay = if has_overlay {
+            let (covered, recoverable) = self.overlay_coverage(options);
+            (1.0 - covered / recoverable).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        
+        let quiet_zone = (self.quiet_zone as f32 / 4.0).min(1.0);
+        
+        // Module gap: penalize half of the empty area left in each cell
+        let fill = match options.shape_module {
+            ModuleShape::Square => 1.0,
+            ModuleShape::Circle => std::f32::consts::PI * 0.45 * 0.45,
+            ModuleShape::RoundedSquare(r) => {
+                let r = r.clamp(0.0, 0.5);
+                1.0 - (4.0 - std::f32::consts::PI) * r * r
+            },
+            // Only run ends are rounded; assume runs average two modules
+            ModuleShape::VerticalBars { radius: r } | ModuleShape::HorizontalBars { radius: r } => {
+                let r = r.clamp(0.0, 0.5);
+                1.0 - (4.0 - std::f32::consts::PI) * r * r / 2.0
+            },
+            // Area of the superellipse quadrant, integrated numerically
+            ModuleShape::Squircle { n } => {
+                let n = n.clamp(1.0, 5.0);
+                const STEPS: usize = 100;
+                (0..STEPS).map(|i| (1.0 - ((i as f32 + 0.5) / STEPS as f32).powf(n)).powf(1.0 / n)).sum::<f32>() / STEPS as f32
+            },
+        };
+        let module_gap = 1.0 - (1.0 - fill) / 2.0;
+        
+        let to_score = |v: f32| (v * 100.0).round() as u8;
+        ScanScore {
+            overall: to_score(contrast * overlay * quiet_zone * module_gap),
+            contrast: to_score(contrast),
+            overlay: to_score(overlay),
+            quiet_zone: to_score(quiet_zone),
+            module_gap: to_score(module_gap),
+        }
+    }
+    
+    // Helper: The cached function module map (indexed r * width + c)
+    fn function_modules(&self) -> &[bool] {
+        self.function.get_or_init(|| {
+            #[cfg(test)]
+            FUNCTION_MAP_COMPUTATIONS.with(|n| n.set(n.get() + 1));
+            self.code.function_modules()
+        })
+    }
+    
+    // Helper: Check if a module is part of a finder pattern
+    pub(crate) fn is_finder_module(c: usize, r: usize, width: usize) -> bool {
+        // Top-Left (0,0), Top-Right (W-7, 0), Bottom-Left (0, W-7)
+        (r < 7 && (c < 7 || c >= width.saturating_sub(7))) || 
+        (r >= width.saturating_sub(7) && c < 7)
+    }
+    
+    // Helper: Check if a module is drawn as a data module: dark, outside the
+    // finders (unless they are not drawn separately) and outside the center safe
+    // zone (unless it is under glass)
+    pub(crate) fn is_drawn_data_module(&self, c: usize, r: usize, options: &FancyOptions) -> bool {
+        let width = self.code.size() as usize;
+        self.code.get_module(c as i32, r as i32)
+            && !(options.custom_finders && Self::is_finder_module(c, r, width))
+            && !(options.overlay_glass.is_none() && Self::is_safe_zone(c, r, width, options))
+    }
+    
+    // Helper: The element drawn at each data position (indexed r * width + c), as its shape
+    // and run length. Timing modules under `solid_timing` are plain squares; bars are
+    // drawn once per run of same-direction bars, from the run's first module; positions
+    // that draw nothing (light, finder, safe zone, inside a bar run) are `None`.
+    pub(crate) fn data_elements(&self, options: &FancyOptions) -> Vec<Option<(ModuleShape, usize)>> {
+        let width = self.code.size() as usize;
+        let timing = self.solid_timing_modules(options);
+        let shape_at = |c: usize, r: usize| {
+            if timing[r * width + c] {
+                return ModuleShape::Square;
+            }
+            match options.module_shape_fn.as_ref().map_or(options.shape_module, |f| f(c, r)) {
+                ModuleShape::RoundedSquare(rad) => ModuleShape::RoundedSquare(
+                    options.corner_radius_map.as_ref().map_or(rad, |f| f(c, r).clamp(0.0, 0.5))),
+                shape => shape,
+            }
+        };
+        let drawn = |c: usize, r: usize| self.is_drawn_data_module(c, r, options);
+        let vertical = |c: usize, r: usize| drawn(c, r) && matches!(shape_at(c, r), ModuleShape::VerticalBars { .. });
+        let horizontal = |c: usize, r: usize| drawn(c, r) && matches!(shape_at(c, r), ModuleShape::HorizontalBars { .. });
+        (0..width * width)
+            .map(|i| {
+                let (c, r) = (i % width, i / width);
+                if !drawn(c, r) {
+                    return None;
+                }
+                let shape = shape_at(c, r);
+                match shape {
+                    ModuleShape::VerticalBars { .. } if r > 0 && vertical(c, r - 1) => None,
+                    ModuleShape::VerticalBars { .. } =>
+                        Some((shape, (r..width).take_while(|&rr| vertical(c, rr)).count())),
+                    ModuleShape::HorizontalBars { .. } if c > 0 && horizontal(c - 1, r) => None,
+                    ModuleShape::HorizontalBars { .. } =>
+                        Some((shape, (c..width).take_while(|&cc| horizontal(cc, r)).count())),
+                    _ => Some((shape, 1)),
+                }
+            })
+            .collect()
+    }
+    
+    // Helper: The (column, row) of the light data-region cells that get a background dot,
+    // skipping function patterns and the safe zone
+    pub(crate) fn background_dot_cells(&self, options: &FancyOptions) -> Vec<(usize, usize)> {
+        let width = self.code.size() as usize;
+        let function = self.function_modules();
+        (0..width * width)
+            .filter(|&i| !function[i])
+            .map(|i| (i % width, i / width))
+            .filter(|&(c, r)| !self.code.get_module(c as i32, r as i32) && !Self::is_safe_zone(c, r, width, options))
+            .collect()
+    }
+    
+    // Helper: Map (indexed r * width + c) of the drawn timing-pattern modules that
+    // `solid_timing` forces to plain squares, leaving alignment patterns on the timing
+    // lines (version 7 and up) in their own shape; all false when the option is off
+    pub(crate) fn solid_timing_modules(&self, options: &FancyOptions) -> Vec<bool> {
+        let width = self.code.size() as usize;
+        if !options.solid_timing {
+            return vec![false; width * width];
+        }
+        let function = self.function_modules();
+        (0..width * width)
+            .map(|i| {
+                let (c, r) = (i % width, i / width);
+                function[i] && (c == 6 || r == 6) && !self.is_alignment_module(c, r)
+                    && self.is_drawn_data_module(c, r, options)
+            })
+            .collect()
+    }
+    
+    // Helper: Reveal index (indexed r * width + c) of each position that draws an element,
+    // numbering those positions 0, 1, 2, ... in the given order
+    fn reveal_ranks(width: usize, order: RevealOrder, draws_element: impl Fn(usize, usize) -> bool) -> Vec<usize> {
+        let mut positions: Vec<(usize, usize)> = (0..width)
+            .flat_map(|r| (0..width).map(move |c| (c, r)))
+            .filter(|&(c, r)| draws_element(c, r))
+            .collect();
+        match order {
+            RevealOrder::Raster => {},
+            RevealOrder::Spiral => {
+                // Chebyshev ring around the center, then clockwise angle starting at the top
+                let center = (width as f32 - 1.0) / 2.0;
+                let key = |&(c, r): &(usize, usize)| {
+                    let (dx, dy) = (c as f32 - center, r as f32 - center);
+                    (dx.abs().max(dy.abs()), dx.atan2(-dy).rem_euclid(std::f32::consts::TAU))
+                };
+                positions.sort_by(|a, b| key(a).partial_cmp(&key(b)).unwrap_or(std::cmp::Ordering::Equal));
+            },
+            RevealOrder::Random(seed) => {
+                // Fisher-Yates shuffle driven by a SplitMix64 generator
+                let mut state = seed;
+                for i in (1..positions.len()).rev() {
+                    state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                    let mut z = state;
+                    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                    z ^= z >> 31;
+                    positions.swap(i, (z % (i as u64 + 1)) as usize);
+                }
+            },
+        }
+        let mut ranks = vec![usize::MAX; width * width];
+        for (i, &(c, r)) in positions.iter().enumerate() {
+            ranks[r * width + c] = i;
+        }
+        ranks
+    }
+    
+    // Helper: Check if module is inside the center safe zone cleared for an overlay
+    pub(crate) fn is_safe_zone(c: usize, r: usize, width: usize, options: &FancyOptions) -> bool {
+        if options.center_image_url.is_none() && options.center_text.is_none() {
+            return false;
+        }
+        let center_idx = width as f32 / 2.0;
+        let safe_size = width as f32 * options.overlay_scale.value();
+        let safe_min = center_idx - (safe_size / 2.0);
+        let safe_max = center_idx + (safe_size / 2.0);
+        let fx = c as f32;
+        let fy = r as f32;
+        fx >= safe_min && fx <= safe_max && fy >= safe_min && fy <= safe_max
+    }
+    
+    // Helper: The fraction of the data area hidden by the safe zone, and the fraction
+    // the error correction blocks can be relied on to recover
+    fn overlay_coverage(&self, options: &FancyOptions) -> (f32, f32) {
+        let hidden = self.overlay_hidden_modules(options);
+        let total = QrCode::get_num_raw_data_modules(self.code.version()) as f32;
+        let budget = Self::overlay_budget(self.code.version(), self.code.error_correction_level());
+        (hidden as f32 / total, budget / total)
+    }
+    
+    // Helper: The number of data modules hidden by the safe zone. Modules under
+    // `overlay_glass` count too: `data_elements` draws them, but the overlay itself is
+    // painted over them, so a scanner can read them no better than cleared ones
+    fn overlay_hidden_modules(&self, options: &FancyOptions) -> usize {
+        let width = self.code.size() as usize;
+        let function = self.function_modules();
+        (0..width * width)
+            .filter(|&i| !function[i] && Self::is_safe_zone(i % width, i / width, width, options))
+            .count()
+    }
+    
+    // Helper: The number of modules an overlay may hide at the version and ECC level,
+    // the same share of `QrCode::max_erasable_modules` for every caller
+    fn overlay_budget(ver: Version, ecl: QrCodeEcc) -> f32 {
+        let info = QrCode::get_block_info(ver, ecl);
+        (info.num_blocks * (info.ecc_codewords_per_block / 2) * 8) as f32 * OVERLAY_BUDGET_FRACTION
+    }
+    
+    // Helper: The lowest ECC level (at least Quartile with an overlay) whose budget at the
+    // version covers the hidden modules, or High if none does
+    fn overlay_minimum_ecc(options: &FancyOptions, ver: Version, hidden: usize) -> QrCodeEcc {
+        if options.center_image_url.is_none() && options.center_text.is_none() {
+            return QrCodeEcc::Low;
+        }
+        [QrCodeEcc::Quartile, QrCodeEcc::High].into_iter()
+            .find(|&ecl| hidden as f32 <= Self::overlay_budget(ver, ecl))
+            .unwrap_or(QrCodeEcc::High)
+    }
+    
+    // Helper: Check if module lies outside the safe zone but within one module of it
+    // (including diagonally), i.e. in the frame colored by `ring_data_color`
+    pub(crate) fn is_safe_zone_frame(c: usize, r: usize, width: usize, options: &FancyOptions) -> bool {
+        if Self::is_safe_zone(c, r, width, options) {
+            return false;
+        }
+        (r.saturating_sub(1)..=(r + 1).min(width - 1))
+            .any(|rr| (c.saturating_sub(1)..=(c + 1).min(width - 1)).any(|cc| Self::is_safe_zone(cc, rr, width, options)))
+    }
+    
+    // Helper: Extra canvas on every side for a ring caption (the code keeps its coordinates)
+    pub(crate) fn ring_padding(&self, options: &FancyOptions) -> usize {
+        let matrix_width = self.code.size() as usize;
+        let full_width = matrix_width + (self.quiet_zone * 2);
+        match &options.ring_text {
+            Some(ring) => {
+                let radius = ring.effective_radius(matrix_width);
+                (radius + RingText::font_size(radius) * 1.5 - full_width as f32 / 2.0).max(0.0).ceil() as usize
+            },
+            None => 0,
+        }
+    }
     
     // Helper: Render the three finder patterns
-    fn render_finder_patterns(
-        svg: &mut String, 
-        matrix_width: usize, 
-        quiet_zone: usize, 
-        options: &FancyOptions
-    ) {
-        let finder_positions = vec![
+    fn render_finder_patterns(&self, svg: &mut String, options: &FancyOptions, fill: &str) {
+        let q = self.quiet_zone as f32;
+        let boxes = self.finder_boxes(options);
+        let push = |svg: &mut String, b: &FinderBox, fill: Option<&str>| {
+            let mut attrs = fill.map(|c| format!(r#" fill="{}""#, c)).unwrap_or_default();
+            if options.debug_attrs {
+                attrs.push_str(&debug_attrs(b.x as usize, b.y as usize));
+            }
+            Self::push_finder_box(svg, b.shape, b.x + q, b.y + q, b.size, b.rx, &attrs, options.pixel_snap);
+        };
+        
+        if let Some(rings) = options.finder_rings.as_ref().filter(|rings| !rings.is_empty()) {
+            for b in boxes.iter() {
+                if let FinderLayer::Ring(i) = b.layer {
+                    push(svg, b, Some(&rings[i]));
+                }
+            }
+            return;
+        }
+
+        // Frames and balls share the finder fill; only the cutouts override it
+        svg.push_str(&format!(r#"<g fill="{}">"#, fill));
+        for b in boxes.iter() {
+            let fill = (b.layer == FinderLayer::Cutout).then_some(options.color_background.as_str());
+            push(svg, b, fill);
+        }
+        svg.push_str("</g>");
+    }
+    
+    // Helper: The boxes of the three finder patterns in drawing order, computed once per
+    // finder style so renders that only change colors reuse them
+    pub(crate) fn finder_boxes(&self, options: &FancyOptions) -> Arc<Vec<FinderBox>> {
+        let style = FinderStyle::of(options);
+        let mut cache = self.finders.lock().unwrap_or_else(|e| e.into_inner());
+        match cache.as_ref() {
+            Some((cached, boxes)) if *cached == style => Arc::clone(boxes),
+            _ => {
+                let boxes = Arc::new(Self::compute_finder_boxes(self.code.size() as usize, options));
+                *cache = Some((style, Arc::clone(&boxes)));
+                boxes
+            },
+        }
+    }
+    
+    fn compute_finder_boxes(matrix_width: usize, options: &FancyOptions) -> Vec<FinderBox> {
+        #[cfg(test)]
+        FINDER_GEOMETRY_COMPUTATIONS.with(|n| n.set(n.get() + 1));
+        let finder_positions = [
             (0, 0), 
             (matrix_width.saturating_sub(7), 0), 
             (0, matrix_width.saturating_sub(7))
         ];
+        
+        // Calculate roundness
+        let r_outer = match options.shape_finder {
+            FinderShape::Square | FinderShape::Dot => 0.0,
+            FinderShape::Rounded(r) => r,
+        };
+        let mut boxes = Vec::new();
+
+        if let Some(rings) = options.finder_rings.as_ref().filter(|rings| !rings.is_empty()) {
+            // Evenly nested rings: with n rings each step is 7/(2n+1), so 3 rings give 7/5/3
+            let step = 7.0 / (2 * rings.len() + 1) as f32;
+            for (fc, fr) in finder_positions {
+                for i in 0..rings.len() {
+                    let inset = i as f32 * step;
+                    let size = 7.0 - 2.0 * inset;
+                    boxes.push(FinderBox {
+                        layer: FinderLayer::Ring(i), shape: options.shape_finder,
+                        x: fc as f32 + inset, y: fr as f32 + inset, size, rx: r_outer * size / 7.0,
+                    });
+                }
+            }
+            return boxes;
+        }
+
+        let ball_shape = options.finder_ball_shape.unwrap_or(options.shape_finder);
+        let r_ball = match ball_shape {
+            FinderShape::Square | FinderShape::Dot => 0.0,
+            FinderShape::Rounded(r) => r * 0.4,
+        };
 
+        let (padding, ball) = Self::finder_proportions(options);
         for (fc, fr) in finder_positions {
-            let x = fc + quiet_zone;
-            let y = fr + quiet_zone;
+            let x = fc as f32;
+            let y = fr as f32;
             
-            // Calculate roundness
-            let r_outer = match options.shape_finder {
-                FinderShape::Square => 0.0,
-                FinderShape::Rounded(r) => r,
-            };
+            // Concentric boxes: outer box (7x7)
+            boxes.push(FinderBox { layer: FinderLayer::Frame, shape: options.shape_finder, x, y, size: 7.0, rx: r_outer });
             
-            // Draw concentric boxes
-            // Outer Box (7x7)
-            svg.push_str(&format!(
-                r#"<rect x="{x}" y="{y}" width="7" height="7" rx="{r}" fill="{color}" />"#, 
-                r=r_outer, 
-                color=options.color_finder
-            ));
-            
-            // Inner Cutout (5x5) - matches background
-            let r_mid = if r_outer > 0.0 { r_outer * 0.7 } else { 0.0 };
+            // Inner cutout (5x5 by default), drawn in the background color
+            let cutout = 7.0 - 2.0 * padding;
+            boxes.push(FinderBox {
+                layer: FinderLayer::Cutout, shape: options.shape_finder,
+                x: x + padding, y: y + padding, size: cutout, rx: r_outer * 0.7 * cutout / 5.0,
+            });
+
+            // Center dot (3x3 by default)
+            let inset = (7.0 - ball) / 2.0;
+            boxes.push(FinderBox {
+                layer: FinderLayer::Ball, shape: ball_shape,
+                x: x + inset, y: y + inset, size: ball, rx: r_ball * ball / 3.0,
+            });
+        }
+        boxes
+    }
+    
+    // Helper: The clamped finder frame thickness and ball size, in modules
+    pub(crate) fn finder_proportions(options: &FancyOptions) -> (f32, f32) {
+        let padding = options.finder_inner_padding.clamp(0.5, 1.5);
+        let ball = (3.0 * options.finder_ball_scale).clamp(1.5, 7.0 - 2.0 * padding - 1.0);
+        (padding, ball)
+    }
+    
+    // Helper: Emit a userSpaceOnUse linear gradient covering the matrix area
+    fn push_gradient_def(svg: &mut String, id: &str, gradient: &Gradient, quiet_zone: usize, matrix_width: usize, snap: Option<NonZeroU32>) {
+        let (x1, y1, x2, y2) = gradient.direction.endpoints(quiet_zone as f32, matrix_width as f32);
+        let (x1, y1, x2, y2) = (snap_px(x1, snap), snap_px(y1, snap), snap_px(x2, snap), snap_px(y2, snap));
+        svg.push_str(&format!(
+            r#"<linearGradient id="{id}" gradientUnits="userSpaceOnUse" x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}"><stop offset="0" stop-color="{start}" /><stop offset="1" stop-color="{end}" /></linearGradient>"#,
+            start=gradient.start,
+            end=gradient.end
+        ));
+    }
+    
+    // Helper: Draw one square-footprint finder element as a rect, or a circle for `FinderShape::Dot`,
+    // with the given extra attributes (each with a leading space)
+    #[allow(clippy::too_many_arguments)]
+    fn push_finder_box(svg: &mut String, shape: FinderShape, x: f32, y: f32, size: f32, rx: f32, attrs: &str, snap: Option<NonZeroU32>) {
+        if shape == FinderShape::Dot {
             svg.push_str(&format!(
-                r#"<rect x="{x}" y="{y}" width="5" height="5" rx="{r}" fill="{color}" />"#, 
-                x=x+1, 
-                y=y+1, 
-                r=r_mid, 
-                color=options.color_background
+                r#"<circle cx="{cx}" cy="{cy}" r="{r}"{attrs} />"#,
+                cx=snap_px(x + size / 2.0, snap),
+                cy=snap_px(y + size / 2.0, snap),
+                r=snap_len(size / 2.0, snap)
             ));
-
-            // Center Dot (3x3)
-            let r_inner = if r_outer > 0.0 { r_outer * 0.4 } else { 0.0 };
+        } else {
             svg.push_str(&format!(
-                r#"<rect x="{x}" y="{y}" width="3" height="3" rx="{r}" fill="{color}" />"#, 
-                x=x+2, 
-                y=y+2, 
-                r=r_inner, 
-                color=options.color_finder
+                r#"<rect x="{x}" y="{y}" width="{size}" height="{size}" rx="{rx}"{attrs} />"#,
+                x=snap_px(x, snap),
+                y=snap_px(y, snap),
+                size=snap_len(size, snap),
+                rx=snap_len(rx, snap)
             ));
         }
     }
@@ -287,38 +1656,52 @@ impl FancyQr {
         center_idx: f32,
         safe_size: f32,
         quiet_zone: usize,
-        options: &FancyOptions
+        options: &FancyOptions,
+        clip_id: Option<&str>
     ) {
         let center_px = center_idx + quiet_zone as f32;
         let size_px = safe_size;
         let start_px = center_px - (size_px / 2.0);
+        let px = |v: f32| snap_px(v, options.pixel_snap);
+        let len = |v: f32| snap_len(v, options.pixel_snap);
+
+        let has_overlay = options.center_image_url.is_some() || options.center_text.is_some();
+        if let Some((color, opacity)) = options.overlay_glass.as_ref().filter(|_| has_overlay) {
+            svg.push_str(&format!(
+                r#"<rect x="{x}" y="{x}" width="{w}" height="{w}" rx="{rx}" fill="{color}" fill-opacity="{o}" />"#,
+                x=px(start_px), w=len(size_px), rx=len(1.0), o=fmt_coord(opacity.clamp(0.0, 1.0))
+            ));
+        }
 
         if let Some(img_href) = &options.center_image_url {
+            let clip = clip_id.map(|id| format!(r#" clip-path="url(#{})""#, id)).unwrap_or_default();
             svg.push_str(&format!(
-                r#"<image x="{x}" y="{y}" width="{w}" height="{h}" href="{href}" preserveAspectRatio="xMidYMid slice" />"#,
-                x=start_px, 
-                y=start_px, 
-                w=size_px, 
-                h=size_px, 
+                r#"<image x="{x}" y="{y}" width="{w}" height="{h}" href="{href}" preserveAspectRatio="xMidYMid slice"{clip} />"#,
+                x=px(start_px), 
+                y=px(start_px), 
+                w=len(size_px), 
+                h=len(size_px), 
                 href=img_href
             ));
         } else if let Some(text) = &options.center_text {
             // Draw a "Label Badge" (white box + text)
             svg.push_str(&format!(
-                r#"<rect x="{x}" y="{y}" width="{w}" height="{h}" rx="1" fill="{bg}" stroke="{fg}" stroke-width="0.2" />"#,
-                x=start_px - 0.5, 
-                y=start_px + (size_px * 0.25),
-                w=size_px + 1.0, 
-                h=size_px * 0.5,
+                r#"<rect x="{x}" y="{y}" width="{w}" height="{h}" rx="{rx}" fill="{bg}" stroke="{fg}" stroke-width="{sw}" />"#,
+                x=px(start_px - 0.5), 
+                y=px(start_px + (size_px * 0.25)),
+                w=len(size_px + 1.0), 
+                h=len(size_px * 0.5),
+                rx=len(1.0),
+                sw=len(0.2),
                 bg=options.color_background, 
                 fg=options.color_data
             ));
             
             svg.push_str(&format!(
                 r#"<text x="{x}" y="{y}" font-family="sans-serif" font-weight="bold" font-size="{sz}" text-anchor="middle" fill="{fg}">{txt}</text>"#,
-                x=center_px, 
-                y=center_px + (size_px * 0.15),
-                sz=size_px * 0.25, 
+                x=px(center_px), 
+                y=px(center_px + (size_px * 0.15)),
+                sz=len(size_px * 0.25), 
                 fg=options.color_data, 
                 txt=text
             ));
@@ -326,6 +1709,265 @@ impl FancyQr {
     }
 }
 
+// Parses a "#RGB" or "#RRGGBB" hex color into its components.
+pub(crate) fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.trim().strip_prefix('#')?;
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    match hex.len() {
+        3 => {
+            let expand = |i: usize| channel(&hex[i..i + 1]).map(|v| v * 17);
+            Some((expand(0)?, expand(1)?, expand(2)?))
+        },
+        6 => Some((channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?)),
+        _ => None,
+    }
+}
+
+// Returns the WCAG contrast ratio between two colors, from 1.0 to 21.0.
+pub(crate) fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f32 {
+    let luminance = |(r, g, b): (u8, u8, u8)| {
+        let linear = |c: u8| {
+            let c = f32::from(c) / 255.0;
+            if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        };
+        0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+    };
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+/// Removes active or external content from an SVG logo before it is embedded.
+/// 
+/// Strips `<script>` elements, event handler attributes (`onload=`, `onclick=`, ...),
+/// and any `href`/`xlink:href` that is not a local fragment (`#id`) or an embedded
+/// raster image (`data:image/png`, `data:image/jpeg`, `data:image/gif`, `data:image/webp`).
+/// Elements are matched by local name, so prefixed forms like `<svg:script>` are caught too.
+/// 
+/// Also drops `DOCTYPE`/`ENTITY` declarations and processing instructions (entities could
+/// expand to markup), `<set>`/`<animate*>` elements that target an `href` or `on*` attribute,
+/// and styles (`style=` attributes and `<style>` elements) with a `url()` other than a local
+/// fragment or embedded raster image, or with CSS escapes that could hide one.
+/// Shapes, paths, styles and the rest of the markup are preserved as-is.
+/// 
+/// This is a conservative filter intended for user-uploaded logos, not a general-purpose
+/// XML sanitizer; the result should still be base64-embedded rather than inlined.
+pub fn sanitize_svg_logo(svg: &str) -> String {
+    let mut out = String::with_capacity(svg.len());
+    let mut rest = svg;
+    
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        
+        // Comments and CDATA are copied verbatim
+        let verbatim_end = if rest.starts_with("<!--") {
+            Some("-->")
+        } else if rest.starts_with("<![CDATA[") {
+            Some("]]>")
+        } else {
+            None
+        };
+        if let Some(end) = verbatim_end {
+            let len = rest.find(end).map_or(rest.len(), |i| i + end.len());
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+        // Declarations (including a DOCTYPE internal subset) and processing instructions are dropped
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = match find_declaration_end(rest) {
+                Some(i) => &rest[i + 1..],
+                None => "",
+            };
+            continue;
+        }
+        
+        let tag_len = match find_tag_end(rest) {
+            Some(i) => i + 1,
+            None => break,  // Unterminated tag: drop the remainder
+        };
+        let tag = &rest[..tag_len];
+        rest = &rest[tag_len..];
+        
+        let closing = tag.starts_with("</");
+        let name: String = tag[if closing { 2 } else { 1 }..].chars()
+            .take_while(|c| !c.is_whitespace() && *c != '/' && *c != '>')
+            .collect();
+        let local = name.rsplit(':').next().unwrap_or("").to_ascii_lowercase();
+        
+        let unsafe_element = match local.as_str() {
+            "script" => true,
+            "set" | "animate" | "animatecolor" | "animatemotion" | "animatetransform" =>
+                !closing && animates_unsafe_attribute(tag),
+            "style" if !closing => !element_body(rest, &name).is_some_and(|body| is_safe_css(&body.0)),
+            _ => false,
+        };
+        if unsafe_element {
+            // Skip the whole element, including its body
+            if !closing && !tag.ends_with("/>") {
+                rest = element_body(rest, &name).map_or("", |(_, after)| after);
+            }
+            continue;
+        }
+        
+        if closing {
+            out.push_str(tag);
+        } else {
+            out.push_str(&sanitize_tag(tag, &name));
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// Returns the index of the '>' that ends the declaration or processing instruction at the
+// start of s, skipping quoted values and a bracketed internal subset.
+fn find_declaration_end(s: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {},
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '[' => depth += 1,
+            None if c == ']' => depth -= 1,
+            None if c == '>' && depth <= 0 => return Some(i),
+            None => {},
+        }
+    }
+    None
+}
+
+// Splits the text after an opening tag into the element body and the text after its
+// closing tag, or returns None if the element is never closed.
+fn element_body<'a>(rest: &'a str, name: &str) -> Option<(String, &'a str)> {
+    let lower = rest.to_ascii_lowercase();
+    let i = lower.find(&format!("</{}", name.to_ascii_lowercase()))?;
+    let after = &rest[i..];
+    let j = after.find('>')?;
+    Some((rest[..i].to_string(), &after[j + 1..]))
+}
+
+// Tests whether a `<set>` or `<animate*>` tag targets an attribute that could run script or
+// load external content. A missing or unparsable target counts as unsafe.
+fn animates_unsafe_attribute(tag: &str) -> bool {
+    let lower = tag.to_ascii_lowercase();
+    let Some(i) = lower.find("attributename") else { return true };
+    let value = lower[i + "attributename".len()..].trim_start();
+    let Some(value) = value.strip_prefix('=') else { return true };
+    let value = value.trim_start().trim_start_matches(['"', '\'']);
+    let target: String = value.chars().take_while(|c| !matches!(c, '"' | '\'' | '>') && !c.is_whitespace()).collect();
+    let target = target.rsplit(':').next().unwrap_or("");
+    target.is_empty() || target == "href" || target.starts_with("on")
+}
+
+// Tests whether CSS only references local fragments or embedded raster images.
+// Escapes are rejected outright, since they could spell out a scheme or a `url(`.
+fn is_safe_css(css: &str) -> bool {
+    let css = css.to_ascii_lowercase();
+    if css.contains('\\') || css.contains("expression(") || css.contains("javascript:") {
+        return false;
+    }
+    css.match_indices("url(").all(|(i, _)| {
+        let target = css[i + 4..].trim_start().trim_start_matches(['"', '\'']);
+        is_safe_reference(target)
+    })
+}
+
+// Returns the index of the '>' that closes the tag at the start of s, skipping quoted values.
+fn find_tag_end(s: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, c) in s.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {},
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(i),
+            None => {},
+        }
+    }
+    None
+}
+
+// Rebuilds an opening tag, keeping only the attributes that are safe to embed.
+fn sanitize_tag(tag: &str, name: &str) -> String {
+    let self_closing = tag.ends_with("/>");
+    let body_end = tag.len() - if self_closing { 2 } else { 1 };
+    let mut attrs = &tag[1 + name.len()..body_end];
+    let mut out = format!("<{}", name);
+    
+    loop {
+        attrs = attrs.trim_start();
+        if attrs.is_empty() {
+            break;
+        }
+        let name_len = attrs.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(attrs.len());
+        let attr_name = &attrs[..name_len];
+        attrs = attrs[name_len..].trim_start();
+        
+        let mut raw = String::new();
+        let mut value = "";
+        if let Some(after_eq) = attrs.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            let (val, len) = match after_eq.chars().next() {
+                Some(q @ ('"' | '\'')) => match after_eq[1..].find(q) {
+                    Some(i) => (&after_eq[1..i + 1], i + 2),
+                    None => (&after_eq[1..], after_eq.len()),
+                },
+                _ => {
+                    let end = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+                    (&after_eq[..end], end)
+                },
+            };
+            value = val;
+            raw = after_eq[..len].to_string();
+            attrs = &after_eq[len..];
+        }
+        
+        if attr_name.is_empty() {
+            // Stray '=' or quote: skip a character to guarantee progress
+            attrs = attrs.get(1..).unwrap_or("");
+            continue;
+        }
+        if is_safe_attribute(attr_name, value) {
+            out.push(' ');
+            out.push_str(attr_name);
+            if !raw.is_empty() {
+                out.push('=');
+                out.push_str(&raw);
+            }
+        }
+    }
+    
+    out.push_str(if self_closing { "/>" } else { ">" });
+    out
+}
+
+fn is_safe_attribute(name: &str, value: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let local = name.rsplit(':').next().unwrap_or("");
+    if local.starts_with("on") {
+        return false;
+    }
+    if local == "href" {
+        return is_safe_reference(&value.trim().to_ascii_lowercase());
+    }
+    if local == "style" {
+        return is_safe_css(value);
+    }
+    true
+}
+
+// Tests whether a lowercase reference is a local fragment or an embedded raster image.
+fn is_safe_reference(value: &str) -> bool {
+    value.starts_with('#') || ["png", "jpeg", "jpg", "gif", "webp"].iter()
+        .any(|fmt| value.starts_with(&format!("data:image/{};", fmt)) || value.starts_with(&format!("data:image/{},", fmt)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -347,12 +1989,1045 @@ mod tests {
     #[test]
     fn test_custom_options() {
         let qr = FancyQr::from_text("Custom").unwrap();
-        let mut options = FancyOptions::default();
-        options.color_data = "#FF0000".to_string();
-        options.shape_module = ModuleShape::Circle;
+        let options = FancyOptions {
+            color_data: "#FF0000".to_string(),
+            shape_module: ModuleShape::Circle,
+            ..FancyOptions::default()
+        };
         let svg = qr.render_svg(&options);
         assert!(svg.contains("#FF0000"));
         assert!(svg.contains("<circle"));
     }
+    
+    #[test]
+    fn test_overlay_scale_range() {
+        assert!(OverlayScale::new(0.35).is_err());
+        assert_eq!(OverlayScale::new(0.2).unwrap().value(), 0.2);
+        assert_eq!(OverlayScale::default().value(), 0.2);
+        assert_eq!(OverlayScale::clamped(0.5), OverlayScale::MAX);
+    }
+    
+    #[test]
+    fn test_solid_fill_grouping() {
+        let qr = FancyQr::from_text("Grouped").unwrap();
+        let options = FancyOptions {
+            color_data: "#123456".to_string(),
+            color_finder: "#654321".to_string(),
+            shape_module: ModuleShape::Circle,
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        assert!(svg.contains(r##"<g fill="#123456">"##));
+        assert!(svg.contains(r##"<g fill="#654321">"##));
+        assert!(svg.split("<circle").skip(1).all(|el| !el[..el.find("/>").unwrap()].contains("fill=")));
+        assert_eq!(svg.matches("#123456").count(), 1);
+        assert_eq!(svg.matches("#654321").count(), 1);
+    }
+    
+    #[test]
+    fn test_borderless_render() {
+        let qr = FancyQr::from_text("Borderless").unwrap().borderless();
+        let width = qr.qrcode().size();
+        let svg = qr.render_svg_default();
+        assert_eq!(qr.quiet_zone(), 0);
+        assert!(svg.contains(&format!(r#"viewBox="0 0 {w} {w}""#, w = width)));
+        assert!(svg.contains(&format!(r#"<rect x="0" y="0" width="{w}" height="{w}""#, w = width)));
+        assert!(svg.contains(r#"<rect x="0" y="0" width="7" height="7""#));
+    }
+    
+    #[test]
+    fn test_sanitize_svg_logo() {
+        let logo = r##"<svg xmlns="http://www.w3.org/2000/svg" onload="alert(1)"><script>alert('x')</script><path d="M0 0h10v10z" fill="#000"/><circle cx="5" cy="5" r="2" onclick='steal()'/><use href="https://evil.example/x.svg#a"/><use href="#local"/></svg>"##;
+        let clean = sanitize_svg_logo(logo);
+        assert!(!clean.contains("<script"));
+        assert!(!clean.contains("alert"));
+        assert!(!clean.contains("onload="));
+        assert!(!clean.contains("onclick="));
+        assert!(!clean.contains("evil.example"));
+        assert!(clean.contains(r##"<path d="M0 0h10v10z" fill="#000"/>"##));
+        assert!(clean.contains(r#"<circle cx="5" cy="5" r="2"/>"#));
+        assert!(clean.contains(r##"<use href="#local"/>"##));
+        assert!(clean.ends_with("</svg>"));
+    }
+    
+    #[test]
+    fn test_sanitize_svg_logo_bypasses() {
+        let entity = r#"<?xml version="1.0"?><!DOCTYPE svg [<!ENTITY x "<script>alert(1)</script>">]><svg><text>&x;</text></svg>"#;
+        let clean = sanitize_svg_logo(entity);
+        assert!(!clean.contains("DOCTYPE") && !clean.contains("ENTITY") && !clean.contains("alert"));
+        assert!(clean.starts_with("<svg><text>"));
+        
+        let prefixed = r#"<svg:svg xmlns:svg="http://www.w3.org/2000/svg"><svg:script>alert(1)</svg:script><svg:rect width="1" height="1"/></svg:svg>"#;
+        let clean = sanitize_svg_logo(prefixed);
+        assert!(!clean.contains("script") && !clean.contains("alert"));
+        assert!(clean.contains(r#"<svg:rect width="1" height="1"/>"#));
+        
+        let animated = r#"<svg><a><set attributeName="href" to="javascript:alert(1)"/><animate attributeName="xlink:href" values="javascript:alert(1)"></animate><animate attributeName="opacity" from="0" to="1" dur="1s"/></a></svg>"#;
+        let clean = sanitize_svg_logo(animated);
+        assert!(!clean.contains("javascript") && !clean.contains("<set") && !clean.contains("</animate>"));
+        assert!(clean.contains(r#"<animate attributeName="opacity""#));
+        
+        let styled = r#"<svg><style>rect { fill: url(javascript:alert(1)) }</style><rect style="fill: url('javascript:alert(1)')"/><circle style="fill: url(#grad)"/></svg>"#;
+        let clean = sanitize_svg_logo(styled);
+        assert!(!clean.contains("javascript"));
+        assert_eq!(clean, r##"<svg><rect/><circle style="fill: url(#grad)"/></svg>"##);
+    }
+    
+    #[test]
+    fn test_scan_reliability() {
+        let qr = FancyQr::from_text("https://example.com").unwrap();
+        let good = qr.scan_reliability(&FancyOptions::default());
+        assert!(good.overall >= 90, "{:?}", good);
+        assert_eq!(good.contrast, 100);
+        
+        let segs = crate::QrSegment::make_segments("https://example.com");
+        let code = QrCode::encode_segments_advanced(&segs, QrCodeEcc::Low,
+            crate::Version::MIN, crate::Version::MAX, None, false).unwrap();
+        let qr = FancyQr::from_qrcode(code);
+        let options = FancyOptions {
+            color_background: "#DDDDDD".to_string(),
+            color_data: "#BBBBBB".to_string(),
+            color_finder: "#BBBBBB".to_string(),
+            center_text: Some("LOGO".to_string()),
+            overlay_scale: OverlayScale::MAX,
+            ..FancyOptions::default()
+        };
+        let bad = qr.scan_reliability(&options);
+        assert!(bad.overall < 20, "{:?}", bad);
+        assert!(bad.contrast < 20);
+        assert_eq!(bad.overlay, 0);
+    }
+    
+    #[test]
+    fn test_finder_rings() {
+        let qr = FancyQr::from_text("Rings").unwrap().borderless();
+        let mut options = FancyOptions::default();
+        let colors = ["#111111", "#EEEEEE", "#FF0000", "#00FF00"];
+        options.finder_rings = Some(colors.iter().map(|c| c.to_string()).collect());
+        let svg = qr.render_svg(&options);
+        for color in colors {
+            assert_eq!(svg.matches(&format!(r#"fill="{}" />"#, color)).count(), 3);
+        }
+        // Outermost ring spans the full footprint, innermost is nested inside it
+        assert!(svg.contains(r##"<rect x="0" y="0" width="7" height="7" rx="0" fill="#111111" />"##));
+        let inner = svg.find(r##"fill="#00FF00""##).unwrap();
+        let outer = svg.find(r##"fill="#111111""##).unwrap();
+        assert!(outer < inner);
+    }
+    
+    #[test]
+    fn test_render_svg_checked() {
+        let qr = FancyQr::from_text("Checked").unwrap();
+        let (svg, warnings) = qr.render_svg_checked(&FancyOptions::default());
+        assert!(svg.ends_with("</svg>"));
+        assert!(warnings.is_empty());
+        
+        let segs = crate::QrSegment::make_segments("Checked");
+        let code = QrCode::encode_segments_advanced(&segs, QrCodeEcc::Low,
+            crate::Version::MIN, crate::Version::MAX, None, false).unwrap();
+        let qr = FancyQr::from_qrcode(code).with_quiet_zone(1);
+        let options = FancyOptions {
+            color_data: "#EEEEEE".to_string(),
+            color_finder: "purple".to_string(),
+            center_text: Some("HI".to_string()),
+            overlay_scale: OverlayScale::MAX,
+            ..FancyOptions::default()
+        };
+        let (svg, warnings) = qr.render_svg_checked(&options);
+        assert!(svg.ends_with("</svg>"));
+        assert!(warnings.iter().any(|w| matches!(w, RenderWarning::OverlayTooLarge(..))));
+        assert!(warnings.iter().any(|w| matches!(w, RenderWarning::LowContrast(c, _) if c == "#EEEEEE")));
+        assert!(warnings.contains(&RenderWarning::UnvalidatedColor("purple".to_string())));
+        assert!(warnings.contains(&RenderWarning::SmallQuietZone(1)));
+    }
+    
+    #[test]
+    fn test_finder_ball_shape_mix() {
+        let qr = FancyQr::from_text("Mix").unwrap().borderless();
+        let mut options = FancyOptions {
+            color_data: "#111111".to_string(),
+            color_finder: "#222222".to_string(),
+            shape_finder: FinderShape::Square,
+            finder_ball_shape: Some(FinderShape::Dot),
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        assert!(svg.contains(r#"<rect x="0" y="0" width="7" height="7" rx="0" />"#));
+        assert!(svg.contains(r#"<circle cx="3.5" cy="3.5" r="1.5" />"#));
+        
+        options.shape_finder = FinderShape::Dot;
+        options.finder_ball_shape = Some(FinderShape::Square);
+        let svg = qr.render_svg(&options);
+        assert!(svg.contains(r#"<circle cx="3.5" cy="3.5" r="3.5" />"#));
+        assert!(svg.contains(r#"<rect x="2" y="2" width="3" height="3" rx="0" />"#));
+    }
+    
+    #[test]
+    fn test_finder_gradient() {
+        let qr = FancyQr::from_text("Gradient").unwrap();
+        let gradient = Gradient {
+            start: "#4d3695".to_string(),
+            end: "#3B82F6".to_string(),
+            direction: GradientDirection::DiagonalTLBR,
+        };
+        let mut options = FancyOptions {
+            gradient_data: Some(gradient.clone()),
+            gradient_finder: Some(gradient.clone()),
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        assert_eq!(svg.matches("<linearGradient").count(), 1);
+        assert_eq!(svg.matches(r#"<g fill="url(#qr-gradient-data)">"#).count(), 2);
+        
+        options.gradient_data = None;
+        options.gradient_finder = Some(Gradient { direction: GradientDirection::Vertical, ..gradient });
+        let svg = qr.render_svg(&options);
+        assert!(svg.contains(r#"<linearGradient id="qr-gradient-finder" gradientUnits="userSpaceOnUse""#));
+        assert!(svg.contains(r#"<g fill="url(#qr-gradient-finder)">"#));
+        assert!(svg.contains(r##"<g fill="#000000">"##));
+    }
+    
+    #[test]
+    fn test_ring_text() {
+        let qr = FancyQr::from_text("https://example.com/pay").unwrap();
+        let mut options = FancyOptions::default();
+        let plain = qr.render_svg(&options);
+        let width = qr.qrcode().size() as usize + qr.quiet_zone() * 2;
+        assert!(plain.contains(&format!(r#"viewBox="0 0 {w} {w}""#, w = width)));
+        
+        options.ring_text = Some(RingText {
+            text: "SCAN TO PAY".to_string(),
+            color: "#111111".to_string(),
+            font: "sans-serif".to_string(),
+            radius: 0.0,
+        });
+        let svg = qr.render_svg(&options);
+        assert!(svg.contains(r#"<path id="qr-ring-path" d="M "#));
+        assert_eq!(svg.matches(" 0 1,1 ").count(), 2);
+        assert!(svg.contains(r##"<textPath href="#qr-ring-path" startOffset="25%">SCAN TO PAY</textPath>"##));
+        assert!(!svg.contains(&format!(r#"viewBox="0 0 {w} {w}""#, w = width)));
+        assert!(svg.contains(r#"viewBox="-"#));
+        
+        options.ring_text.as_mut().unwrap().text = "Fish & Chips <Deals>".to_string();
+        let svg = qr.render_svg(&options);
+        assert!(svg.contains(r#"startOffset="25%">Fish &amp; Chips &lt;Deals&gt;</textPath>"#));
+        assert!(!svg.contains("Fish & Chips"));
+    }
+    
+    #[test]
+    fn test_bar_shapes() {
+        let qr = FancyQr::from_text("https://example.com").unwrap();
+        let size = qr.qrcode().size() as usize;
+        let qz = qr.quiet_zone();
+        let mut options = FancyOptions::default();
+        
+        // Find three vertically stacked data modules that form a complete run
+        let (c, r) = (0..size).flat_map(|c| (1..size - 3).map(move |r| (c, r)))
+            .find(|&(c, r)| !qr.is_drawn_data_module(c, r - 1, &options)
+                && (r..r + 3).all(|rr| qr.is_drawn_data_module(c, rr, &options))
+                && !qr.is_drawn_data_module(c, r + 3, &options))
+            .expect("code has a vertical run of three");
+        let tall = format!(r#"<rect x="{}" y="{}" width="1" height="3" rx="0.5" />"#, c + qz, r + qz);
+        
+        options.shape_module = ModuleShape::VerticalBars { radius: 0.5 };
+        let svg = qr.render_svg(&options);
+        assert!(svg.contains(&tall));
+        for rr in r + 1..r + 3 {
+            assert!(!svg.contains(&format!(r#"x="{}" y="{}""#, c + qz, rr + qz)));
+        }
+        
+        // Every drawn module is covered exactly once
+        let heights: usize = svg.match_indices(r#"height=""#)
+            .skip(1) // background
+            .take_while(|(i, _)| *i < svg.find("</g>").unwrap())
+            .map(|(i, m)| svg[i + m.len()..].split('"').next().unwrap().parse::<usize>().unwrap())
+            .sum();
+        let drawn = (0..size).flat_map(|c| (0..size).map(move |r| (c, r)))
+            .filter(|&(c, r)| qr.is_drawn_data_module(c, r, &options))
+            .count();
+        assert_eq!(heights, drawn);
+        
+        options.shape_module = ModuleShape::HorizontalBars { radius: 0.5 };
+        let svg = qr.render_svg(&options);
+        assert!(!svg.contains(&tall));
+        assert!(svg.contains(r#"height="1" rx="0.5""#));
+    }
+    
+    #[test]
+    fn test_overlay_covers_alignment() {
+        let segs = crate::QrSegment::make_segments("https://example.com");
+        let v10 = crate::Version::new(10);
+        let code = QrCode::encode_segments_advanced(&segs, QrCodeEcc::High, v10, v10, None, true).unwrap();
+        let qr = FancyQr::from_qrcode(code);
+        let mut options = FancyOptions {
+            center_text: Some("LOGO".to_string()),
+            overlay_scale: OverlayScale::MAX,
+            ..FancyOptions::default()
+        };
+        // The center alignment pattern of version 10 is the 5x5 block around (28, 28)
+        assert!(qr.validate(&options).contains(&RenderWarning::OverlayCoversFunctionPattern(25)));
+        
+        let qr = FancyQr::from_text("Hi").unwrap();
+        assert_eq!(qr.qrcode().version(), crate::Version::MIN);
+        options.overlay_scale = OverlayScale::default();
+        assert!(!qr.validate(&options).iter().any(|w| matches!(w, RenderWarning::OverlayCoversFunctionPattern(_))));
+    }
+    
+    #[test]
+    fn test_pixel_snap() {
+        let qr = FancyQr::from_text("Snap").unwrap();
+        let mut options = FancyOptions {
+            pixel_snap: NonZeroU32::new(10),
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        let width = (qr.qrcode().size() as usize + qr.quiet_zone() * 2) * 10;
+        assert!(svg.contains(&format!(r#"viewBox="0 0 {w} {w}""#, w = width)));
+        for attr in [" x=", " y=", " width=", " height=", " rx="] {
+            for (i, m) in svg.match_indices(attr) {
+                let value = svg[i + m.len() + 1..].split('"').next().unwrap();
+                assert!(value.parse::<i64>().is_ok(), "{}{:?}", attr, value);
+            }
+        }
+        
+        options.shape_module = ModuleShape::Circle;
+        options.center_text = Some("HI".to_string());
+        let svg = qr.render_svg(&options);
+        for attr in [" cx=", " cy=", " r=", " stroke-width="] {
+            for (i, m) in svg.match_indices(attr) {
+                let value = svg[i + m.len() + 1..].split('"').next().unwrap();
+                assert!(value.parse::<i64>().is_ok(), "{}{:?}", attr, value);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_pixel_snap_keeps_small_shapes() {
+        let qr = FancyQr::from_text("Snap").unwrap();
+        let mut options = FancyOptions {
+            pixel_snap: NonZeroU32::new(1),
+            shape_module: ModuleShape::Circle,
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        let circles = svg.matches("<circle ").count();
+        assert!(circles > 0);
+        assert_eq!(svg.matches(r#" r="1""#).count(), circles);
+        assert!(!svg.contains(r#" r="0""#));
+        
+        options.shape_module = ModuleShape::RoundedSquare(0.1);
+        let svg = qr.render_svg(&options);
+        assert!(svg.contains(r#"width="1" height="1" rx="1""#));
+        assert!(!svg.contains(r#"width="0""#));
+    }
+    
+    #[test]
+    fn test_from_segments() {
+        let segs = vec![
+            QrSegment::make_numeric("0123456789012"),
+            QrSegment::make_bytes("-shop".as_bytes()),
+        ];
+        let qr = FancyQr::from_segments(&segs, QrCodeEcc::High).unwrap();
+        assert!(qr.qrcode() == &QrCode::encode_segments(&segs, QrCodeEcc::High).unwrap());
+        let svg = qr.render_svg_default();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>"));
+    }
+    
+    #[test]
+    fn test_solid_timing() {
+        let qr = FancyQr::from_text("Timing").unwrap();
+        let qz = qr.quiet_zone();
+        let size = qr.qrcode().size() as usize;
+        let mut options = FancyOptions {
+            shape_module: ModuleShape::Circle,
+            ..FancyOptions::default()
+        };
+        let timing_rect = |c: usize| format!(r#"<rect x="{}" y="{}" width="1" height="1" />"#, c + qz, 6 + qz);
+        
+        let svg = qr.render_svg(&options);
+        assert!(!svg.contains(&timing_rect(8)));
+        
+        options.solid_timing = true;
+        let svg = qr.render_svg(&options);
+        for c in (8..size - 8).step_by(2) {
+            assert!(svg.contains(&timing_rect(c)), "timing module {}", c);
+            assert!(svg.contains(&format!(r#"<rect x="{}" y="{}" width="1" height="1" />"#, 6 + qz, c + qz)));
+        }
+        assert!(svg.contains("<circle "));
+        
+        // Version 7 has alignment patterns centered on the timing lines
+        let qr = FancyQr::from_qrcode(QrCode::encode_text_min_version("Timing", QrCodeEcc::Low, crate::Version::new(7)).unwrap());
+        let svg = qr.render_svg(&options);
+        assert!(svg.contains(&timing_rect(18)));
+        for c in [20, 22, 24] {
+            assert!(!svg.contains(&timing_rect(c)), "alignment module {}", c);
+            assert!(svg.contains(&format!(r#"<circle cx="{}.5" cy="{}.5" r="0.45" />"#, c + qz, 6 + qz)));
+            assert!(svg.contains(&format!(r#"<circle cx="{}.5" cy="{}.5" r="0.45" />"#, 6 + qz, c + qz)));
+        }
+    }
+    
+    #[test]
+    fn test_theme_color_override() {
+        let base = FancyOptions::from_theme(Theme::GradientLogo);
+        let options = FancyOptions::from_theme(Theme::GradientLogo).with_colors("", "#123456", "");
+        assert_eq!(options.color_data, "#123456");
+        assert_eq!(options.color_background, base.color_background);
+        assert_eq!(options.color_finder, base.color_finder);
+        assert_eq!(options.shape_module, ModuleShape::Circle);
+        assert_eq!(options.shape_finder, FinderShape::Rounded(2.0));
+        assert_eq!(options.overlay_scale, base.overlay_scale);
+        
+        assert_eq!(FancyOptions::from_theme(Theme::MinimalFinders).overlay_scale, OverlayScale::NONE);
+    }
+    
+    #[test]
+    fn test_module_role() {
+        let qr = FancyQr::from_text_with_ecc("Hello", QrCodeEcc::Low).unwrap();
+        assert_eq!(qr.qrcode().version().value(), 1);
+        let qz = qr.quiet_zone();
+        let width = qr.qrcode().size() as usize;
+        assert_eq!(qr.module_role(qz, qz), ModuleRole::Finder);
+        assert_eq!(qr.module_role(qz + width - 1, qz), ModuleRole::Finder);
+        assert_eq!(qr.module_role(qz, qz + width - 1), ModuleRole::Finder);
+        assert_eq!(qr.module_role(qz + width / 2, qz + width / 2), ModuleRole::Data);
+        assert_eq!(qr.module_role(qz + 10, qz + 6), ModuleRole::Timing);
+        assert_eq!(qr.module_role(qz + 8, qz + 2), ModuleRole::Format);
+        assert_eq!(qr.module_role(qz + 8, qz + width - 8), ModuleRole::Format);
+        assert_eq!(qr.module_role(0, 0), ModuleRole::Quiet);
+        assert_eq!(qr.module_role(qz + width, qz), ModuleRole::Quiet);
+        
+        // Version 7 has alignment patterns on the timing rows and version information
+        let qr = FancyQr::from_qrcode(QrCode::encode_text_min_version("Hello", QrCodeEcc::Low, crate::Version::new(7)).unwrap());
+        assert_eq!(qr.module_role(qz + 22, qz + 22), ModuleRole::Alignment);
+        assert_eq!(qr.module_role(qz + 22, qz + 6), ModuleRole::Alignment);
+        assert_eq!(qr.module_role(qz + 2, qz + 34), ModuleRole::Format);
+        // Version information next to the alignment grid positions hidden under the finders
+        for (x, y) in [(36, 4), (4, 36), (34, 0), (0, 34), (34, 5), (5, 34)] {
+            assert_eq!(qr.module_role(qz + x, qz + y), ModuleRole::Format, "({}, {})", x, y);
+        }
+        assert_eq!(qr.module_role(qz + 38, qz + 22), ModuleRole::Alignment);
+    }
+    
+    #[test]
+    fn test_reveal_order() {
+        let qr = FancyQr::from_text("Reveal").unwrap();
+        let reveal_indices = |svg: &str| -> Vec<usize> {
+            svg.split(r#"data-reveal=""#).skip(1)
+                .map(|rest| rest.split('"').next().unwrap().parse().unwrap())
+                .collect()
+        };
+        let mut options = FancyOptions::default();
+        assert!(!qr.render_svg(&options).contains("data-reveal"));
+        
+        options.reveal_order = Some(RevealOrder::Raster);
+        let svg = qr.render_svg(&options);
+        let indices = reveal_indices(&svg);
+        assert_eq!(indices[0], 0);
+        assert!(indices.windows(2).all(|w| w[0] < w[1]));
+        assert!(svg.contains(r#"data-reveal="0" style="animation-delay:calc(0 * var(--qr-reveal-step, 10ms))""#));
+        let count = indices.len();
+        
+        // Other orders number the same elements, just differently
+        for order in [RevealOrder::Spiral, RevealOrder::Random(42)] {
+            options.reveal_order = Some(order);
+            let mut indices = reveal_indices(&qr.render_svg(&options));
+            assert_ne!(indices, (0..count).collect::<Vec<_>>());
+            indices.sort_unstable();
+            assert_eq!(indices, (0..count).collect::<Vec<_>>());
+        }
+        assert_eq!(qr.render_svg(&options), qr.render_svg(&options));
+        
+        // Bars are numbered once per run
+        options.shape_module = ModuleShape::VerticalBars { radius: 0.5 };
+        let svg = qr.render_svg(&options);
+        assert_eq!(reveal_indices(&svg).len(), svg.matches("<rect").count() - 1 - 9);
+    }
+    
+    #[test]
+    fn test_renders_reuse_layout() {
+        let qr = FancyQr::from_text("https://example.com").unwrap();
+        let mut options = FancyOptions {
+            solid_timing: true,
+            ..FancyOptions::default()
+        };
+        let before = FUNCTION_MAP_COMPUTATIONS.with(|n| n.get());
+        let first = qr.render_svg(&options);
+        options.color_data = "#4d3695".to_string();
+        let second = qr.render_svg(&options);
+        assert_eq!(FUNCTION_MAP_COMPUTATIONS.with(|n| n.get()), before + 1);
+        assert_ne!(first, second);
+        assert_eq!(first.replace("#000000", "#4d3695"), second.replace("#000000", "#4d3695"));
+        
+        // Clones share the already computed layout
+        let copy = qr.clone();
+        assert_eq!(copy.render_svg(&options), second);
+        assert_eq!(FUNCTION_MAP_COMPUTATIONS.with(|n| n.get()), before + 1);
+    }
+    
+    #[test]
+    fn test_renders_reuse_finder_geometry() {
+        let qr = FancyQr::from_text("Finder cache").unwrap();
+        let finders = |svg: &str| svg[svg.rfind("<g fill=").unwrap()..].to_string();
+        let mut options = FancyOptions {
+            shape_finder: FinderShape::Rounded(1.5),
+            ..FancyOptions::default()
+        };
+        let before = FINDER_GEOMETRY_COMPUTATIONS.with(|n| n.get());
+        let first = qr.render_svg(&options);
+        options.color_data = "#4d3695".to_string();
+        let second = qr.render_svg(&options);
+        assert_eq!(finders(&first), finders(&second));
+        options.color_finder = "#8B5CF6".to_string();
+        qr.render_svg(&options);
+        assert_eq!(FINDER_GEOMETRY_COMPUTATIONS.with(|n| n.get()), before + 1);
+        
+        // A different finder style is computed anew
+        options.shape_finder = FinderShape::Dot;
+        assert!(qr.render_svg(&options).contains("<circle"));
+        assert_eq!(FINDER_GEOMETRY_COMPUTATIONS.with(|n| n.get()), before + 2);
+        
+        // Clones share the cached boxes
+        qr.clone().render_svg(&options);
+        assert_eq!(FINDER_GEOMETRY_COMPUTATIONS.with(|n| n.get()), before + 2);
+    }
+    
+    #[test]
+    fn test_low_ecc_with_overlay() {
+        let mut options = FancyOptions {
+            center_text: Some("LOGO".to_string()),
+            overlay_scale: OverlayScale::new(0.25).unwrap(),
+            ..FancyOptions::default()
+        };
+        
+        // Without boosting, since a short text would otherwise be raised anyway
+        let segs = QrSegment::make_segments("https://example.com");
+        let code = QrCode::encode_segments_advanced(&segs, QrCodeEcc::Low, crate::Version::MIN, crate::Version::MAX, None, false).unwrap();
+        let qr = FancyQr::from_qrcode(code);
+        assert!(qr.validate(&options).contains(&RenderWarning::LowEccForOverlay(QrCodeEcc::Low, QrCodeEcc::Quartile)));
+        
+        let qr = FancyQr::from_text_with_options("https://example.com", QrCodeEcc::Low, &options).unwrap();
+        assert!(qr.qrcode().error_correction_level() >= QrCodeEcc::Quartile);
+        assert!(!qr.validate(&options).iter().any(|w| matches!(w, RenderWarning::LowEccForOverlay(..))));
+        
+        options.center_text = None;
+        assert_eq!(options.minimum_ecc(), QrCodeEcc::Low);
+    }
+    
+    #[test]
+    fn test_minimum_ecc_matches_validate() {
+        let mut options = FancyOptions {
+            center_text: Some("LOGO".to_string()),
+            ..FancyOptions::default()
+        };
+        for scale in [0.1, 0.15, 0.2, 0.22, 0.25] {
+            options.overlay_scale = OverlayScale::new(scale).unwrap();
+            let minimum = options.minimum_ecc();
+            for text in ["Hi", "https://example.com", &"x".repeat(300)] {
+                let qr = FancyQr::from_text_with_options(text, QrCodeEcc::Low, &options).unwrap();
+                assert!(qr.qrcode().error_correction_level() >= minimum);
+                let warnings = qr.validate(&options);
+                let overlay_warning = warnings.iter()
+                    .any(|w| matches!(w, RenderWarning::OverlayTooLarge(..) | RenderWarning::LowEccForOverlay(..)));
+                let (covered, recoverable) = qr.overlay_coverage(&options);
+                // Accepted overlays get no warning; only one that fails even at High may
+                assert!(!overlay_warning || (minimum == QrCodeEcc::High && covered > recoverable),
+                    "scale {} text {:?}: {:?}", scale, text, warnings);
+            }
+        }
+        options.overlay_scale = OverlayScale::new(0.2).unwrap();
+        let qr = FancyQr::from_text_with_options("https://example.com", QrCodeEcc::Low, &options).unwrap();
+        assert!(qr.validate(&options).is_empty());
+    }
+    
+    #[test]
+    fn test_svg_id_namespaces_defs() {
+        let gradient = Gradient {
+            start: "#4d3695".to_string(),
+            end: "#3B82F6".to_string(),
+            direction: GradientDirection::Horizontal,
+        };
+        let render = |id: &str| {
+            let options = FancyOptions {
+                gradient_data: Some(gradient.clone()),
+                svg_id: Some(id.to_string()),
+                svg_class: Some("qr".to_string()),
+                ..FancyOptions::default()
+            };
+            FancyQr::from_text("Namespaced").unwrap().render_svg(&options)
+        };
+        let ids = |svg: &str| -> Vec<String> {
+            svg.split(r#" id=""#).skip(1).map(|rest| rest.split('"').next().unwrap().to_string()).collect()
+        };
+        let (a, b) = (render("first"), render("second"));
+        assert!(a.starts_with(r#"<svg id="first" class="qr" viewBox="#));
+        assert!(a.contains(r#"<linearGradient id="first-qr-gradient-data""#));
+        assert!(a.contains(r#"fill="url(#first-qr-gradient-data)""#));
+        let (ids_a, ids_b) = (ids(&a), ids(&b));
+        assert_eq!(ids_a.len(), 2);
+        assert!(ids_a.iter().all(|id| !ids_b.contains(id)));
+    }
+    
+    #[test]
+    fn test_module_shape_fn() {
+        let qr = FancyQr::from_text("Mixed shapes").unwrap();
+        let options = FancyOptions {
+            module_shape_fn: Some(Box::new(|c, _| if c % 2 == 0 { ModuleShape::Circle } else { ModuleShape::Square })),
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        let data = svg.split(r##"<g fill="#000000">"##).nth(1).unwrap().split("</g>").next().unwrap();
+        assert!(data.contains("<circle cx="));
+        assert!(data.contains("<rect x="));
+        
+        // Every drawn module gets exactly one element of its column's shape
+        let drawn = (0..qr.qrcode().size() as usize)
+            .flat_map(|r| (0..qr.qrcode().size() as usize).map(move |c| (c, r)))
+            .filter(|&(c, r)| qr.is_drawn_data_module(c, r, &options))
+            .collect::<Vec<_>>();
+        assert_eq!(data.matches("<circle").count(), drawn.iter().filter(|&&(c, _)| c % 2 == 0).count());
+        assert_eq!(data.matches("<rect").count(), drawn.iter().filter(|&&(c, _)| c % 2 == 1).count());
+    }
+    
+    #[test]
+    fn test_quiet_zone_decoration() {
+        let qr = FancyQr::from_text("Sticker").unwrap().with_quiet_zone(5);
+        let full = (qr.qrcode().size() as usize + 10) as f32;
+        let mut options = FancyOptions {
+            quiet_zone_decoration: Some(QuietZoneDecoration::InnerLine { color: "#CCCCCC".to_string(), width: 0.5 }),
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        // The stroke spans 0..0.5 from the edge, well clear of the modules at 5
+        let frame = format!(r##"<rect x="0.25" y="0.25" width="{s}" height="{s}" rx="1" fill="none" stroke="#CCCCCC" stroke-width="0.5" />"##, s = full - 0.5);
+        assert!(svg.contains(&frame));
+        assert!(qr.validate(&options).is_empty());
+        
+        options.quiet_zone_decoration = Some(QuietZoneDecoration::Dots { color: "#CCCCCC".to_string() });
+        let svg = qr.render_svg(&options);
+        let dots = svg.split(r##"<g fill="#CCCCCC">"##).nth(1).unwrap().split("</g>").next().unwrap();
+        let centers: Vec<f32> = dots.split('"').skip(1).step_by(2).take(dots.matches("<circle").count() * 3)
+            .map(|v| v.parse().unwrap())
+            .collect();
+        assert_eq!(dots.matches("<circle").count(), 4 * (full as usize - 1));
+        for xy in centers.chunks(3) {
+            let (cx, cy) = (xy[0], xy[1]);
+            // Each dot lies in the outermost ring, outside the module area
+            assert!(cx < 1.0 || cx > full - 1.0 || cy < 1.0 || cy > full - 1.0);
+        }
+        
+        // With the standard 4-module quiet zone, the decoration leaves only 3 clear
+        let qr = FancyQr::from_text("Sticker").unwrap();
+        assert!(qr.validate(&options).contains(&RenderWarning::SmallQuietZone(3)));
+    }
+    
+    #[test]
+    fn test_ring_data_color() {
+        let qr = FancyQr::from_text_with_ecc("Framed logo", QrCodeEcc::High).unwrap();
+        let width = qr.qrcode().size() as usize;
+        let mut options = FancyOptions {
+            center_text: Some("AA".to_string()),
+            overlay_scale: OverlayScale::new(0.25).unwrap(),
+            ring_data_color: Some("#FF0000".to_string()),
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        let data = svg.split(r##"<g fill="#000000">"##).nth(1).unwrap().split("</g>").next().unwrap();
+        
+        let mut framed = 0;
+        for r in 0..width {
+            for c in 0..width {
+                if !qr.is_drawn_data_module(c, r, &options) {
+                    continue;
+                }
+                let rect = format!(r#"<rect x="{}" y="{}" width="1" height="1""#, c + qr.quiet_zone(), r + qr.quiet_zone());
+                let element = data.split(&rect).nth(1).unwrap().split("/>").next().unwrap();
+                let adjacent = (r.saturating_sub(1)..=r + 1).any(|rr| (c.saturating_sub(1)..=c + 1)
+                    .any(|cc| FancyQr::is_safe_zone(cc, rr, width, &options)));
+                // Modules next to the safe zone use the ring color, the rest inherit `color_data`
+                assert_eq!(element.contains(r##"fill="#FF0000""##), adjacent, "module ({}, {})", c, r);
+                framed += usize::from(adjacent);
+            }
+        }
+        assert!(framed > 0);
+        
+        // Without an overlay there is no safe zone, so nothing is framed
+        options.center_text = None;
+        assert!(!qr.render_svg(&options).contains("#FF0000"));
+    }
+    
+    #[test]
+    fn test_parse_style_spec() {
+        let options = FancyOptions::parse("data=#123456, finder=#abcdef,module=circle,finder_shape=dot,overlay=0.2").unwrap();
+        assert_eq!(options.color_data, "#123456");
+        assert_eq!(options.color_finder, "#abcdef");
+        assert_eq!(options.color_background, "#FFFFFF");
+        assert_eq!(options.shape_module, ModuleShape::Circle);
+        assert_eq!(options.shape_finder, FinderShape::Dot);
+        assert_eq!(options.overlay_scale, OverlayScale::new(0.2).unwrap());
+        assert!(FancyOptions::try_from("").is_ok());
+        
+        let err = |spec: &str| FancyOptions::parse(spec).err().unwrap();
+        assert_eq!(err("data=#12345G"), ParseStyleError::InvalidColor("#12345G".to_string()));
+        assert_eq!(err("background=white"), ParseStyleError::InvalidColor("white".to_string()));
+        assert_eq!(err("module=hexagon"), ParseStyleError::UnknownShape("hexagon".to_string()));
+        assert_eq!(err("finder_shape=circle"), ParseStyleError::UnknownShape("circle".to_string()));
+        assert_eq!(err("overlay=0.5"), ParseStyleError::OutOfRange("0.5".to_string()));
+        assert_eq!(err("overlay=big"), ParseStyleError::OutOfRange("big".to_string()));
+        assert_eq!(err("color=#000"), ParseStyleError::UnknownKey("color".to_string()));
+        assert_eq!(err("module"), ParseStyleError::Syntax("module".to_string()));
+    }
+    
+    #[test]
+    fn test_plain_finders() {
+        let qr = FancyQr::from_text("Plain finders").unwrap();
+        let width = qr.qrcode().size() as usize;
+        let mut options = FancyOptions {
+            shape_module: ModuleShape::Circle,
+            color_finder: "#FF0000".to_string(),
+            custom_finders: false,
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        // No finder boxes or finder color, only the data group
+        assert!(!svg.contains("#FF0000"));
+        assert_eq!(svg.matches("<rect").count(), 1); // the background
+        
+        // Every dark finder module is a circle of its own
+        let data = svg.split(r##"<g fill="#000000">"##).nth(1).unwrap().split("</g>").next().unwrap();
+        let finder_dark = (0..width * width)
+            .filter(|&i| FancyQr::is_finder_module(i % width, i / width, width))
+            .filter(|&i| qr.qrcode().get_module((i % width) as i32, (i / width) as i32))
+            .collect::<Vec<_>>();
+        for &i in &finder_dark {
+            let circle = format!(r#"<circle cx="{}" cy="{}""#, i % width + 4, i / width + 4);
+            assert!(data.replace(".5", "").contains(&circle), "finder module {}", i);
+        }
+        
+        options.custom_finders = true;
+        let styled = qr.render_svg(&options);
+        let styled_data = styled.split(r##"<g fill="#000000">"##).nth(1).unwrap().split("</g>").next().unwrap();
+        assert_eq!(data.matches("<circle").count(), styled_data.matches("<circle").count() + finder_dark.len());
+    }
+    
+    #[test]
+    fn test_circular_logo_clip() {
+        let qr = FancyQr::from_text_with_ecc("Round logo", QrCodeEcc::High).unwrap();
+        let mut options = FancyOptions {
+            center_image_url: Some("logo.png".to_string()),
+            ..FancyOptions::default()
+        };
+        assert!(!qr.render_svg(&options).contains("clip"));
+        
+        options.overlay_shape = OverlayShape::Circle;
+        options.svg_id = Some("brand".to_string());
+        let svg = qr.render_svg(&options);
+        let defs = svg.split("<defs>").nth(1).unwrap().split("</defs>").next().unwrap();
+        assert!(defs.contains(r#"<clipPath id="brand-qr-logo-clip"><circle "#));
+        let image = svg.split("<image ").nth(1).unwrap().split("/>").next().unwrap();
+        assert!(image.contains(r##"clip-path="url(#brand-qr-logo-clip)""##));
+        
+        // Text overlays are not clipped
+        options.center_image_url = None;
+        options.center_text = Some("AA".to_string());
+        assert!(!qr.render_svg(&options).contains("clip"));
+    }
+    
+    #[test]
+    fn test_finder_proportions() {
+        let qr = FancyQr::from_text("Finder weight").unwrap().borderless();
+        let mut options = FancyOptions::default();
+        let cutout = |svg: &str| {
+            let rect = svg.split(r##"fill="#FFFFFF""##).nth(2).unwrap().rsplit("<rect").next().unwrap().to_string();
+            rect.split("width=\"").nth(1).unwrap().split('"').next().unwrap().parse::<f32>().unwrap()
+        };
+        assert_eq!(cutout(&qr.render_svg(&options)), 5.0);
+        
+        // A thicker frame leaves a smaller cutout
+        options.finder_inner_padding = 1.4;
+        let svg = qr.render_svg(&options);
+        assert!((cutout(&svg) - 4.2).abs() < 1e-4);
+        assert!(svg.contains(r#"<rect x="1.4" y="1.4""#));
+        
+        // The ball scales around the same center but never touches the frame
+        options.finder_inner_padding = 1.0;
+        options.finder_ball_scale = 0.5;
+        assert!(qr.render_svg(&options).contains(r#"<rect x="2.75" y="2.75" width="1.5" height="1.5""#));
+        options.finder_ball_scale = 10.0;
+        assert_eq!(FancyQr::finder_proportions(&options), (1.0, 4.0));
+    }
+    
+    #[test]
+    fn test_overlay_glass() {
+        let qr = FancyQr::from_text_with_ecc("Under glass", QrCodeEcc::High).unwrap();
+        let width = qr.qrcode().size() as usize;
+        let mut options = FancyOptions {
+            center_text: Some("AA".to_string()),
+            ..FancyOptions::default()
+        };
+        let count = |svg: &str| svg.split(r##"<g fill="#000000">"##).nth(1).unwrap().split("</g>").next().unwrap().matches("<rect").count();
+        let cleared = count(&qr.render_svg(&options));
+        
+        options.overlay_glass = Some(("#FFFFFF".to_string(), 0.6));
+        let svg = qr.render_svg(&options);
+        assert!(svg.contains(r##"fill="#FFFFFF" fill-opacity="0.6" />"##));
+        // The modules under the glass are drawn
+        let covered = (0..width * width)
+            .filter(|&i| qr.qrcode().get_module((i % width) as i32, (i / width) as i32))
+            .filter(|&i| FancyQr::is_safe_zone(i % width, i / width, width, &options))
+            .count();
+        assert!(covered > 0);
+        assert_eq!(count(&svg), cleared + covered);
+        
+        // No panel without an overlay to sit behind
+        options.center_text = None;
+        assert!(!qr.render_svg(&options).contains("fill-opacity"));
+    }
+    
+    #[test]
+    fn test_overlay_glass_coverage() {
+        let qr = FancyQr::from_text_with_ecc("Under glass", QrCodeEcc::Medium).unwrap();
+        let width = qr.qrcode().size() as usize;
+        let mut options = FancyOptions {
+            center_text: Some("LOGO".to_string()),
+            overlay_scale: OverlayScale::new(0.3).unwrap(),
+            ..FancyOptions::default()
+        };
+        let in_zone = |i: usize, options: &FancyOptions| FancyQr::is_safe_zone(i % width, i / width, width, options);
+        
+        // Without glass the safe zone draws nothing, and its data modules count as hidden
+        let hidden = qr.overlay_hidden_modules(&options);
+        assert!(hidden > 0);
+        assert!(qr.data_elements(&options).iter().enumerate().all(|(i, e)| !in_zone(i, &options) || e.is_none()));
+        let cleared = qr.validate(&options);
+        assert!(cleared.iter().any(|w| matches!(w, RenderWarning::OverlayTooLarge(..))));
+        
+        // Under glass the modules are drawn, but the overlay still covers them
+        options.overlay_glass = Some(("#FFFFFF".to_string(), 0.4));
+        assert!(qr.data_elements(&options).iter().enumerate().any(|(i, e)| in_zone(i, &options) && e.is_some()));
+        assert_eq!(qr.overlay_hidden_modules(&options), hidden);
+        assert_eq!(qr.validate(&options), cleared);
+    }
+    
+    #[test]
+    fn test_corner_radius_map() {
+        let qr = FancyQr::from_text("Flowing corners").unwrap();
+        let width = qr.qrcode().size() as f32;
+        let mut options = FancyOptions {
+            shape_module: ModuleShape::RoundedSquare(0.1),
+            corner_radius_map: Some(Box::new(move |_, r| r as f32 / width)),
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        let data = svg.split(r##"<g fill="#000000">"##).nth(1).unwrap().split("</g>").next().unwrap();
+        let radii: std::collections::BTreeSet<String> = data.split(r#"rx=""#).skip(1)
+            .map(|rest| rest.split('"').next().unwrap().to_string())
+            .collect();
+        assert!(radii.len() > 10);
+        assert!(radii.contains("0.5")); // the lower rows saturate
+        
+        // Finders are drawn as before
+        let finders = |svg: &str| svg.split("</g>").nth(1).unwrap().to_string();
+        options.corner_radius_map = None;
+        assert_eq!(finders(&svg), finders(&qr.render_svg(&options)));
+    }
+    
+    #[test]
+    fn test_color_same_as_background() {
+        let qr = FancyQr::from_text("Blank square").unwrap();
+        let mut options = FancyOptions {
+            color_data: "#fff".to_string(),
+            ..FancyOptions::default()
+        };
+        let warnings = qr.validate(&options);
+        assert_eq!(warnings, vec![RenderWarning::SameAsBackground("#fff".to_string())]);
+        
+        options.color_data = "#000000".to_string();
+        options.color_background = "white".to_string();
+        options.color_finder = "White".to_string();
+        assert!(qr.validate(&options).contains(&RenderWarning::SameAsBackground("White".to_string())));
+        assert!(!qr.validate(&options).contains(&RenderWarning::SameAsBackground("#000000".to_string())));
+    }
+    
+    #[test]
+    fn test_rotation() {
+        let qr = FancyQr::from_text("Rotated").unwrap();
+        let mut options = FancyOptions::default();
+        let upright = qr.render_svg(&options);
+        assert!(!upright.contains("rotate("));
+        
+        options.rotation = Rotation::Deg90;
+        let svg = qr.render_svg(&options);
+        let w = qr.qrcode().size() + 8;
+        assert!(svg.contains(&format!(r#"viewBox="0 0 {w} {w}""#)));
+        assert!(svg.contains(&format!(r#"<g transform="rotate(90 {c} {c})">"#, c = w as f32 / 2.0)));
+        assert!(svg.ends_with("</g></svg>"));
+    }
+    
+    #[test]
+    fn test_accessible() {
+        let qr = FancyQr::from_text("https://example.com/a11y").unwrap();
+        let options = FancyOptions::accessible();
+        assert!(qr.validate(&options).is_empty());
+        let score = qr.scan_reliability(&options);
+        assert_eq!((score.contrast, score.quiet_zone, score.module_gap), (100, 100, 100));
+        
+        let svg = qr.render_svg(&options);
+        assert!(!svg.contains("Gradient"));
+        assert!(!svg.contains("<image") && !svg.contains("<text") && !svg.contains("clipPath"));
+    }
+    
+    #[test]
+    fn test_debug_attrs() {
+        let qr = FancyQr::from_text("Debug").unwrap();
+        let mut options = FancyOptions::default();
+        assert!(!qr.render_svg(&options).contains("data-x"));
+        
+        options.debug_attrs = true;
+        let svg = qr.render_svg(&options);
+        // The top-left finder frame and ball
+        assert!(svg.contains(r#"<rect x="4" y="4" width="7" height="7" rx="0" data-x="0" data-y="0" />"#));
+        assert!(svg.contains(r#"data-x="2" data-y="2""#));
+        // Data modules, with the finders drawn as plain modules too
+        options.custom_finders = false;
+        let svg = qr.render_svg(&options);
+        assert!(svg.contains(r#"<rect x="4" y="4" width="1" height="1" data-x="0" data-y="0" />"#));
+        assert_eq!(svg.matches("data-x=").count(), svg.matches("<rect").count() - 1);
+    }
+    
+    #[test]
+    fn test_to_data_uri() {
+        let qr = FancyQr::from_text("Embedded").unwrap();
+        let options = FancyOptions::from_theme(Theme::Premium);
+        let uri = qr.to_data_uri(&options);
+        let encoded = uri.strip_prefix("data:image/svg+xml;base64,").unwrap();
+        let decoded = crate::render::base64_decode(encoded).unwrap();
+        assert_eq!(String::from_utf8(decoded).unwrap(), qr.render_svg(&options));
+    }
+    
+    #[test]
+    fn test_background_dots() {
+        let qr = FancyQr::from_text("Dotted").unwrap();
+        let mut options = FancyOptions::default();
+        assert!(!qr.render_svg(&options).contains("#DDDDDD"));
+        
+        options.background_dots = Some(("#DDDDDD".to_string(), 0.15));
+        let svg = qr.render_svg(&options);
+        let group = svg.split(r##"<g fill="#DDDDDD">"##).nth(1).unwrap().split("</g>").next().unwrap();
+        let cells = qr.background_dot_cells(&options);
+        assert!(!cells.is_empty());
+        assert_eq!(group.matches("<circle").count(), cells.len());
+        
+        let width = qr.qrcode().size() as usize;
+        for &(c, r) in &cells {
+            assert!(!qr.qrcode().get_module(c as i32, r as i32));
+            assert!(!FancyQr::is_finder_module(c, r, width));
+        }
+        // The light ring inside the top-left finder gets no dot
+        assert!(!group.contains(r#"cx="5.5" cy="5.5""#));
+        assert!(group.contains(r#"r="0.15""#));
+    }
+    
+    #[test]
+    fn test_squircle() {
+        assert!((squircle_handle(2.0) - 0.552_284_8).abs() < 1e-4);
+        assert_eq!(squircle_handle(1.0), 0.0);
+        assert!(squircle_handle(4.0) > squircle_handle(3.0));
+        
+        let qr = FancyQr::from_text("Squircle").unwrap();
+        let mut options = FancyOptions {
+            shape_module: ModuleShape::Squircle { n: 4.0 },
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        let drawn = qr.data_elements(&options).iter().filter(|e| e.is_some()).count();
+        assert_eq!(svg.matches("<path d=\"M").count(), drawn);
+        // Finders keep their own shape
+        assert!(svg.contains(r#"<rect x="4" y="4" width="7" height="7" rx="0" />"#));
+        
+        // The handles move toward the corners as n grows
+        let mut first_path = |n: f32| {
+            options.shape_module = ModuleShape::Squircle { n };
+            let svg = qr.render_svg(&options);
+            let start = svg.find("<path d=\"M").unwrap();
+            svg[start..].split("C").nth(1).unwrap().to_string()
+        };
+        let (round, square) = (first_path(2.0), first_path(5.0));
+        assert_ne!(round, square);
+        // The y of the first handle, in "x,y x,y x,y"
+        let handle = |curve: &str| curve.split([',', ' ']).nth(1).unwrap().parse::<f32>().unwrap();
+        assert!(handle(&square) > handle(&round));
+        
+        assert_eq!(FancyOptions::parse("module=squircle").unwrap().shape_module, ModuleShape::Squircle { n: 4.0 });
+    }
+    
+    #[test]
+    fn test_reencode_with_ecc() {
+        let text = "https://example.com/products/12345?ref=qr-campaign";
+        let high = FancyQr::from_text(text).unwrap().with_quiet_zone(2);
+        let low = high.reencode_with_ecc(QrCodeEcc::Low).unwrap();
+        assert!(low.qrcode().version() < high.qrcode().version());
+        assert_eq!(low.quiet_zone(), 2);
+        assert!(*low.qrcode() == QrCode::encode_text(text, QrCodeEcc::Low).unwrap());
+        
+        let back = low.reencode_with_ecc(QrCodeEcc::High).unwrap();
+        assert!(*back.qrcode() == *high.qrcode());
+    }
+    
+    #[test]
+    fn test_palette() {
+        let qr = FancyQr::from_text("A colorful code with enough modules for every color").unwrap();
+        let colors = ["#E63946", "#1D3557", "#2A9D8F", "#6A4C93"];
+        let mut options = FancyOptions {
+            palette: Some((colors.iter().map(|c| c.to_string()).collect(), 42)),
+            ..FancyOptions::default()
+        };
+        
+        let svg = qr.render_svg(&options);
+        assert_eq!(svg, qr.render_svg(&options));
+        for color in colors {
+            assert!(svg.contains(&format!(r#"fill="{}" />"#, color)), "{}", color);
+        }
+        // Finders keep the finder color
+        assert!(svg.contains(r##"<g fill="#000000"><rect x="4" y="4" width="7" height="7" rx="0" />"##));
+        
+        options.palette = Some((colors.iter().map(|c| c.to_string()).collect(), 7));
+        assert_ne!(svg, qr.render_svg(&options));
+        options.custom_finders = false;
+        assert!(qr.render_svg(&options).contains(r#"<rect x="4" y="4" width="1" height="1" />"#));
+        options.custom_finders = true;
+        
+        options.palette = Some((vec!["#FFFFFF".to_string()], 7));
+        assert!(qr.validate(&options).contains(&RenderWarning::SameAsBackground("#FFFFFF".to_string())));
+    }
+    
+    #[test]
+    fn test_deterministic_output() {
+        let qr = FancyQr::from_text("https://example.com/stable").unwrap();
+        let options = FancyOptions {
+            shape_module: ModuleShape::Squircle { n: 4.0 },
+            shape_finder: FinderShape::Rounded(1.3),
+            finder_rings: Some(vec!["#111111".to_string(), "#555555".to_string(), "#999999".to_string()]),
+            center_text: Some("LOGO".to_string()),
+            overlay_scale: OverlayScale::new(0.23).unwrap(),
+            overlay_glass: Some(("#FFFFFF".to_string(), 0.35)),
+            ring_text: Some(RingText {
+                text: "STABLE".to_string(),
+                color: "#111111".to_string(),
+                font: "sans-serif".to_string(),
+                radius: 0.0,
+            }),
+            rotation: Rotation::Deg90,
+            ..FancyOptions::default()
+        };
+        let svg = qr.render_svg(&options);
+        assert_eq!(svg, qr.render_svg(&options));
+        assert!(svg.is_ascii());
+        
+        // Every number prints with at most 3 decimals
+        for number in svg.split(|c: char| !(c.is_ascii_digit() || c == '.')).filter(|n| n.contains('.')) {
+            assert!(number.split('.').nth(1).unwrap().len() <= 3, "{}", number);
+        }
+        assert!(svg.contains("<path d=\"M"));
+    }
 }
-
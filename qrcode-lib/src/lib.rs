@@ -112,8 +112,28 @@ mod segment;
 mod qrcode;
 pub mod fancy;
 pub mod render;
+pub mod content;
+#[cfg(feature = "raster")]
+pub mod raster;
 
 // Re-export public API
-pub use types::{QrCodeEcc, Version, Mask, DataTooLong};
+pub use types::{QrCodeEcc, Version, Mask, DataTooLong, ParseEccError, StructureError, QrError};
 pub use segment::{QrSegment, QrSegmentMode, BitBuffer};
-pub use qrcode::QrCode;
+pub use qrcode::{QrCode, BlockInfo, InterleaveDebug, Encodable, mask_pattern, CAPACITY_BITS};
+
+/// The commonly used types, for a single glob import.
+/// 
+/// ```rust
+/// use qrcode_lib::prelude::*;
+/// 
+/// let qr = FancyQr::from_text_with_ecc("https://example.com", QrCodeEcc::High).unwrap();
+/// let mut options = FancyOptions::default();
+/// options.shape_module = ModuleShape::Circle;
+/// options.shape_finder = FinderShape::Rounded(1.5);
+/// let svg = qr.render_svg(&options);
+/// assert!(svg.starts_with("<svg"));
+/// ```
+pub mod prelude {
+	pub use crate::{QrCode, QrCodeEcc, QrSegment, Version, Mask, QrError};
+	pub use crate::fancy::{FancyQr, FancyOptions, ModuleShape, FinderShape, Gradient, GradientDirection, OverlayScale};
+}
@@ -9,8 +9,8 @@
 //! Core QR Code generation logic.
 
 use std::convert::TryFrom;
-use crate::types::{QrCodeEcc, Version, Mask, DataTooLong, get_bit};
-use crate::segment::{QrSegment, BitBuffer};
+use crate::types::{QrCodeEcc, Version, Mask, DataTooLong, StructureError, QrError, get_bit};
+use crate::segment::{QrSegment, QrSegmentMode, BitBuffer};
 
 /// A QR Code symbol, which is a type of two-dimension barcode.
 /// 
@@ -59,7 +59,9 @@ impl QrCode {
 	/// Returns a QR Code representing the given Unicode text string at the given error correction level.
 	/// 
 	/// As a conservative upper bound, this function is guaranteed to succeed for strings that have 738 or fewer Unicode
-	/// code points (not UTF-8 code units) if the low error correction level is used. The smallest possible
+	/// code points (not UTF-8 code units) if the low error correction level is used. Text that is not purely
+	/// numeric/alphanumeric is encoded in byte mode, where the actual limit is 2953 UTF-8 bytes; emoji take
+	/// 4 bytes and most CJK characters 3, so use `fits_text()` for an exact answer. The smallest possible
 	/// QR Code version is automatically chosen for the output. The ECC level of the result may be higher than
 	/// the ecl argument if it can be done without increasing the version.
 	/// 
@@ -70,6 +72,112 @@ impl QrCode {
 		QrCode::encode_segments(&segs, ecl)
 	}
 	
+	/// Returns a QR Code representing the given Unicode text string exactly as `encode_text()`
+	/// would, but rejects text that is likely to differ from what the caller sees.
+	/// 
+	/// Leading or trailing whitespace (e.g. a space pasted after a URL) and control
+	/// characters other than line breaks are invisible in the input, yet change the
+	/// encoded content and often the segment mode. Trim or clean the text first, or
+	/// use `encode_text()` if they are intended.
+	/// 
+	/// Returns `Err` naming the first such character, or if the data is too long to
+	/// fit in any version at the given ECC level.
+	pub fn encode_text_strict(text: &str, ecl: QrCodeEcc) -> Result<Self,QrError> {
+		if let Some(c) = text.chars().next().filter(|c| c.is_whitespace())
+				.or_else(|| text.chars().next_back().filter(|c| c.is_whitespace())) {
+			return Err(QrError::SurroundingWhitespace(c));
+		}
+		if let Some(c) = text.chars().find(|&c| c.is_control() && c != '\n' && c != '\r') {
+			return Err(QrError::NonPrintableCharacter(c));
+		}
+		Ok(QrCode::encode_text(text, ecl)?)
+	}
+	
+	/// Returns a QR Code for the given text exactly as `encode_text()` would, along with
+	/// the segments that were chosen to encode it.
+	/// 
+	/// Each entry is a segment's mode and its character count, in encoding order. This is
+	/// useful for finding out why some text produced a larger symbol than expected.
+	/// 
+	/// Returns `Err` if the data is too long to fit in any version at the given ECC level.
+	pub fn encode_text_explained(text: &str, ecl: QrCodeEcc) -> Result<(Self, Vec<(QrSegmentMode, usize)>), DataTooLong> {
+		let segs: Vec<QrSegment> = QrSegment::make_segments(text);
+		let qr = QrCode::encode_segments(&segs, ecl)?;
+		Ok((qr, segs.iter().map(|seg| (seg.mode(), seg.num_chars())).collect()))
+	}
+	
+	/// Returns a QR Code representing the given Unicode text string at the highest ECC level
+	/// from `preferred` down to `min` that the text fits in, along with the ECC level of the
+	/// result. As in `encode_text()`, that level may be boosted if it costs no extra version.
+	/// 
+	/// This automates the common fallback of lowering the error correction level when the
+	/// text is too long. If `min` is above `preferred`, only `preferred` is tried.
+	/// 
+	/// Returns `Err` if the data is too long to fit in any version even at the `min` level.
+	pub fn encode_text_best_effort(text: &str, preferred: QrCodeEcc, min: QrCodeEcc) -> Result<(Self, QrCodeEcc), DataTooLong> {
+		let segs: Vec<QrSegment> = QrSegment::make_segments(text);
+		let mut error = DataTooLong::SegmentTooLong;  // Replaced by the first attempt
+		for ecl in [QrCodeEcc::High, QrCodeEcc::Quartile, QrCodeEcc::Medium, QrCodeEcc::Low] {
+			if ecl > preferred || (ecl < min && ecl != preferred) {
+				continue;
+			}
+			match QrCode::encode_segments(&segs, ecl) {
+				Ok(qr) => {
+					let ecl: QrCodeEcc = qr.error_correction_level();
+					return Ok((qr, ecl));
+				},
+				Err(e) => error = e,
+			}
+		}
+		Err(error)
+	}
+	
+	/// Returns a QR Code representing the given Unicode text string, using at least the given version.
+	/// 
+	/// This is `encode_text()` with a minimum size, for when labels need consistent dimensions
+	/// regardless of payload length. A larger version is still chosen if the data requires it.
+	/// The ECC level of the result may be higher than the ecl argument if it can be done
+	/// without increasing the version.
+	/// 
+	/// Returns `Err` if the data is too long to fit in any version at the given ECC level.
+	pub fn encode_text_min_version(text: &str, ecl: QrCodeEcc, min: Version) -> Result<Self,DataTooLong> {
+		let segs: Vec<QrSegment> = QrSegment::make_segments(text);
+		QrCode::encode_segments_advanced(&segs, ecl, min, Version::MAX, None, true)
+	}
+	
+	/// Returns the smallest QR Code representing the given Unicode text string.
+	/// 
+	/// Unlike `encode_text()`, which uses one mode for the whole text, this switches between
+	/// numeric, alphanumeric and byte mode as `QrSegment::make_segments_optimally()` does,
+	/// so e.g. an order number inside a URL costs 3.3 bits per digit instead of 8. Characters
+	/// outside the alphanumeric set (lowercase, control characters, non-ASCII) use byte mode.
+	/// The ECC level of the result may be higher than the ecl argument if it can be done
+	/// without increasing the version.
+	/// 
+	/// Returns `Err` if the data is too long to fit in any version at the given ECC level.
+	pub fn smart_encode(input: &str, ecl: QrCodeEcc) -> Result<Self,DataTooLong> {
+		// The optimal split only changes where the character count field widths do
+		let mut result = Err(DataTooLong::SegmentTooLong);
+		for (minver, maxver) in [(1, 9), (10, 26), (27, 40)] {
+			let segs: Vec<QrSegment> = QrSegment::make_segments_optimally(input, Version::new(maxver));
+			result = QrCode::encode_segments_advanced(&segs, ecl, Version::new(minver), Version::new(maxver), None, true);
+			if result.is_ok() {
+				break;
+			}
+		}
+		result
+	}
+	
+	/// Returns a QR Code representing the given text or binary data at the given error correction level.
+	/// 
+	/// Text is encoded as by `encode_text()` and binary data as by `encode_binary()`.
+	/// 
+	/// Returns a wrapped `QrCode` if successful, or `Err` if the
+	/// data is too long to fit in any version at the given ECC level.
+	pub fn encode<E: Encodable>(data: E, ecl: QrCodeEcc) -> Result<Self,DataTooLong> {
+		data.encode(ecl)
+	}
+	
 	/// Returns a QR Code representing the given binary data at the given error correction level.
 	/// 
 	/// This function always encodes using the binary segment mode, not any text mode. The maximum number of
@@ -83,6 +191,42 @@ impl QrCode {
 		QrCode::encode_segments(&segs, ecl)
 	}
 	
+	/// Returns a QR Code representing the given byte chunks, each preceded by an ECI
+	/// designator with its assignment value, so the character set can switch mid-stream.
+	/// 
+	/// The smallest possible QR Code version is automatically chosen for the output. The ECC level
+	/// of the result may be higher than the ecl argument if it can be done without increasing the version.
+	/// 
+	/// Returns `Err` if the data is too long to fit in any version at the given ECC level.
+	/// Panics if an assignment value is 1000000 or more.
+	pub fn encode_binary_eci(chunks: &[(u32, &[u8])], ecl: QrCodeEcc) -> Result<Self,DataTooLong> {
+		let segs: Vec<QrSegment> = QrSegment::make_eci_bytes(chunks);
+		QrCode::encode_segments(&segs, ecl)
+	}
+	
+	/// Tests whether the given text can be encoded at the given error correction level.
+	/// 
+	/// This is exactly when `encode_text()` would succeed, but without building the symbol.
+	/// Byte-mode text is measured in UTF-8 bytes, so multi-byte characters such as emoji
+	/// are accounted for exactly.
+	pub fn fits_text(text: &str, ecl: QrCodeEcc) -> bool {
+		QrCode::remaining_capacity_after(text, ecl).is_some()
+	}
+	
+	/// Returns how many more bytes of data would fit after the given text, using the
+	/// largest version at the given error correction level, or `None` if the text itself
+	/// does not fit.
+	/// 
+	/// The result is the unused data capacity in bits divided by 8, rounded down. Since text
+	/// that is not purely numeric/alphanumeric is encoded in byte mode, this is the number
+	/// of further UTF-8 bytes (not characters) that could be appended to such text.
+	pub fn remaining_capacity_after(text: &str, ecl: QrCodeEcc) -> Option<usize> {
+		let segs: Vec<QrSegment> = QrSegment::make_segments(text);
+		let usedbits: usize = QrSegment::get_total_bits(&segs, Version::MAX)?;
+		let capacitybits: usize = QrCode::get_num_data_codewords(Version::MAX, ecl) * 8;
+		capacitybits.checked_sub(usedbits).map(|bits| bits / 8)
+	}
+	
 	/*---- Static factory functions (mid level) ----*/
 	
 	/// Returns a QR Code representing the given segments at the given error correction level.
@@ -113,19 +257,51 @@ impl QrCode {
 	/// This is a mid-level API; the high-level API is `encode_text()` and `encode_binary()`.
 	/// 
 	/// Returns a wrapped `QrCode` if successful, or `Err` if the data is too
-	/// long to fit in any version in the given range at the given ECC level.
-	pub fn encode_segments_advanced(segs: &[QrSegment], mut ecl: QrCodeEcc,
+	/// long to fit in any version in the given range at the given ECC level,
+	/// or if minversion is greater than maxversion.
+	pub fn encode_segments_advanced(segs: &[QrSegment], ecl: QrCodeEcc,
 			minversion: Version, maxversion: Version, mask: Option<Mask>, boostecl: bool)
 			-> Result<Self,DataTooLong> {
+		let masks: &[Mask] = match &mask {
+			Some(m) => std::slice::from_ref(m),
+			None => &ALL_MASKS,
+		};
+		QrCode::encode_segments_with_masks(segs, ecl, minversion, maxversion, masks, boostecl)
+	}
+	
+	/// Returns a QR Code representing the given segments like `encode_segments_advanced()`,
+	/// but with the mask automatically chosen only among `allowed_masks`, e.g. the masks
+	/// that scan best when the code will be rotated or mirrored.
+	/// 
+	/// The mask with the lowest penalty score among the allowed ones is used; with a single
+	/// allowed mask, that mask is forced. An empty slice allows all eight masks.
+	/// 
+	/// Returns a wrapped `QrCode` if successful, or `Err` if the data is too
+	/// long to fit in any version in the given range at the given ECC level,
+	/// or if minversion is greater than maxversion.
+	pub fn encode_segments_with_masks(segs: &[QrSegment], ecl: QrCodeEcc,
+			minversion: Version, maxversion: Version, allowed_masks: &[Mask], boostecl: bool)
+			-> Result<Self,DataTooLong> {
+		let (version, ecl, datacodewords) = QrCode::make_data_codewords(segs, ecl, minversion, maxversion, boostecl)?;
+		Ok(QrCode::encode_codewords_among(version, ecl, &datacodewords, allowed_masks))
+	}
+	
+	// Returns the version, ECC level, and data codewords (padded to capacity) that
+	// encode_segments_with_masks() would use, or Err if the data does not fit.
+	fn make_data_codewords(segs: &[QrSegment], mut ecl: QrCodeEcc,
+			minversion: Version, maxversion: Version, boostecl: bool)
+			-> Result<(Version,QrCodeEcc,Vec<u8>),DataTooLong> {
 		
-		assert!(minversion <= maxversion, "Invalid value");
+		if minversion > maxversion {
+			return Err(DataTooLong::InvalidVersionRange(minversion, maxversion));
+		}
 		
 		// Find the minimal version number to use
 		let mut version: Version = minversion;
 		let datausedbits: usize = loop {
 			let datacapacitybits: usize = QrCode::get_num_data_codewords(version, ecl) * 8;
 			let dataused: Option<usize> = QrSegment::get_total_bits(segs, version);
-			if dataused.map_or(false, |n| n <= datacapacitybits) {
+			if dataused.is_some_and(|n| n <= datacapacitybits) {
 				break dataused.unwrap();
 			} else if version >= maxversion {
 				return Err(match dataused {
@@ -171,13 +347,20 @@ impl QrCode {
 		}
 		
 		// Pack bits into bytes in big endian
-		let mut datacodewords = vec![0u8; bb.0.len() / 8];
-		for (i, &bit) in bb.0.iter().enumerate() {
-			datacodewords[i >> 3] |= u8::from(bit) << (7 - (i & 7));
-		}
-		
-		// Create the QR Code object
-		Ok(QrCode::encode_codewords(version, ecl, &datacodewords, mask))
+		Ok((version, ecl, bb.into_bytes()))
+	}
+	
+	/// Returns the eight QR Codes for the given segments with masks 0 to 7 forced,
+	/// all at exactly the given version and ECC level.
+	/// 
+	/// The codes carry the same data and function patterns (apart from the format
+	/// information, which records the mask), which makes them useful for showing
+	/// how masking works. The ECC level is not boosted.
+	/// 
+	/// Returns `Err` if the data does not fit in the given version at the given ECC level.
+	pub fn all_masks(segs: &[QrSegment], ecl: QrCodeEcc, version: Version) -> Result<[Self; 8], DataTooLong> {
+		let (version, ecl, datacodewords) = QrCode::make_data_codewords(segs, ecl, version, version, false)?;
+		Ok(std::array::from_fn(|m| QrCode::encode_codewords(version, ecl, &datacodewords, Some(Mask::new(m as u8)))))
 	}
 	
 	/*---- Constructor (low level) ----*/
@@ -187,9 +370,19 @@ impl QrCode {
 	/// 
 	/// This is a low-level API that most users should not use directly.
 	/// A mid-level API is the `encode_segments()` function.
-	pub fn encode_codewords(ver: Version, ecl: QrCodeEcc, datacodewords: &[u8], mut msk: Option<Mask>) -> Self {
+	pub fn encode_codewords(ver: Version, ecl: QrCodeEcc, datacodewords: &[u8], msk: Option<Mask>) -> Self {
+		let masks: &[Mask] = match &msk {
+			Some(m) => std::slice::from_ref(m),
+			None => &ALL_MASKS,
+		};
+		QrCode::encode_codewords_among(ver, ecl, datacodewords, masks)
+	}
+	
+	// Creates a new QR Code with the mask of lowest penalty among the given ones
+	// (all eight if empty); a single given mask is used without scoring.
+	fn encode_codewords_among(ver: Version, ecl: QrCodeEcc, datacodewords: &[u8], masks: &[Mask]) -> Self {
 		// Initialize fields
-		let size = usize::from(ver.value()) * 4 + 17;
+		let size = ver.module_count() as usize;
 		let mut result = Self {
 			version: ver,
 			size: size as i32,
@@ -205,10 +398,13 @@ impl QrCode {
 		result.draw_codewords(&allcodewords);
 		
 		// Do masking
-		if msk.is_none() {
-			let mut minpenalty = std::i32::MAX;
-			for i in 0u8 .. 8 {
-				let i = Mask::new(i);
+		let masks: &[Mask] = if masks.is_empty() { &ALL_MASKS } else { masks };
+		let mut msk: Option<Mask> = None;
+		if let [only] = masks {
+			msk = Some(*only);
+		} else {
+			let mut minpenalty = i32::MAX;
+			for &i in masks {
 				result.apply_mask(i);
 				result.draw_format_bits(i);
 				let penalty: i32 = result.get_penalty_score();
@@ -241,6 +437,14 @@ impl QrCode {
 		self.size
 	}
 	
+	/// Returns the width (and height) of this QR Code rendered with the given border
+	/// in modules and module size in pixels, i.e. `(size + 2 * border) * module_size`.
+	/// 
+	/// This is the viewBox size of the `render` SVG functions.
+	pub fn size_for_render(&self, border: i32, module_size: i32) -> i32 {
+		(self.size + border * 2) * module_size
+	}
+	
 	/// Returns this QR Code's error correction level.
 	pub fn error_correction_level(&self) -> QrCodeEcc {
 		self.errorcorrectionlevel
@@ -251,6 +455,51 @@ impl QrCode {
 		self.mask
 	}
 	
+	/// Returns how this QR Code's codewords are split into error correction blocks.
+	/// 
+	/// This describes the layout used to interleave the data and ECC codewords.
+	pub fn block_structure(&self) -> BlockInfo {
+		QrCode::get_block_info(self.version, self.errorcorrectionlevel)
+	}
+	
+	/// Returns how many modules of the data area can be hidden (e.g. by a logo) while
+	/// the symbol remains decodable, based on the error correction block structure.
+	/// 
+	/// A scanner does not know which modules are hidden, so each block corrects at most
+	/// half as many codewords as it has ECC codewords. Interleaving spreads a covered area
+	/// evenly across the blocks, so the budget is the sum over all blocks, at 8 modules
+	/// per codeword. This is an upper bound: a covered area that cuts through more codewords
+	/// than it fills, or damage elsewhere, lowers it.
+	pub fn max_erasable_modules(&self) -> usize {
+		let info: BlockInfo = self.block_structure();
+		info.num_blocks * (info.ecc_codewords_per_block / 2) * 8
+	}
+	
+	/// Splits the given data codewords into error correction blocks exactly as encoding
+	/// does, and returns each block's data and ECC codewords along with the final
+	/// interleaved codeword sequence that is drawn into the symbol.
+	/// 
+	/// This is a tool for learning and testing how interleaving works.
+	/// 
+	/// Panics if the data length is not the number of data codewords of the version and ECC level.
+	pub fn interleave_debug(data: &[u8], ver: Version, ecl: QrCodeEcc) -> InterleaveDebug {
+		let info: BlockInfo = QrCode::get_block_info(ver, ecl);
+		let blocks: Vec<Vec<u8>> = QrCode::make_ecc_blocks(data, ver, ecl);
+		let interleaved: Vec<u8> = QrCode::interleave_blocks(&blocks, info);
+		let mut data_blocks = Vec::<Vec<u8>>::with_capacity(info.num_blocks);
+		let mut ecc_blocks = Vec::<Vec<u8>>::with_capacity(info.num_blocks);
+		for (i, block) in blocks.into_iter().enumerate() {
+			let datlen: usize = if i < info.num_short_blocks {
+				info.short_block_data_codewords
+			} else {
+				info.long_block_data_codewords
+			};
+			data_blocks.push(block[.. datlen].to_vec());
+			ecc_blocks.push(block[block.len() - info.ecc_codewords_per_block ..].to_vec());
+		}
+		InterleaveDebug { data_blocks, ecc_blocks, interleaved }
+	}
+	
 	/// Returns the color of the module (pixel) at the given coordinates,
 	/// which is `false` for light or `true` for dark.
 	/// 
@@ -260,6 +509,291 @@ impl QrCode {
 		(0 .. self.size).contains(&x) && (0 .. self.size).contains(&y) && self.module(x, y)
 	}
 	
+	/// Returns the color of the module at the given coordinates, or `None` if
+	/// they are out of bounds.
+	/// 
+	/// Unlike `get_module()`, this distinguishes a light module from a position off the grid.
+	pub fn get_module_checked(&self, x: i32, y: i32) -> Option<bool> {
+		if (0 .. self.size).contains(&x) && (0 .. self.size).contains(&y) {
+			Some(self.module(x, y))
+		} else {
+			None
+		}
+	}
+	
+	/// Returns the modules as rows of booleans, indexed as `matrix[y][x]` (`true` for dark).
+	pub fn to_matrix(&self) -> Vec<Vec<bool>> {
+		(0 .. self.size).map(|y| (0 .. self.size).map(|x| self.module(x, y)).collect()).collect()
+	}
+	
+	/// Returns the data codewords of this QR Code, read back from its modules.
+	/// 
+	/// The mask is removed, the codewords are read in placement order, de-interleaved,
+	/// and the ECC codewords are dropped. No error correction is performed, so this is
+	/// exactly the data that was given to `encode_codewords()`.
+	pub fn data_codewords(&self) -> Vec<u8> {
+		let info: BlockInfo = self.block_structure();
+		self.codeword_blocks().iter().enumerate()
+			.flat_map(|(j, block)| {
+				let datlen: usize = if j < info.num_short_blocks { info.short_block_data_codewords } else { info.long_block_data_codewords };
+				block[.. datlen].iter().copied()
+			})
+			.collect()
+	}
+	
+	// Reads the codewords back from the modules and returns each error correction block:
+	// its data followed by its ECC codewords, without the padding of make_ecc_blocks().
+	fn codeword_blocks(&self) -> Vec<Vec<u8>> {
+		let isfunction: Vec<bool> = self.function_modules();
+		
+		// Read the codewords in the zigzag order of draw_codewords()
+		let mut raw = vec![0u8; QrCode::get_num_raw_data_modules(self.version) / 8];
+		let mut i: usize = 0;
+		let mut right: i32 = self.size - 1;
+		while right >= 1 {
+			if right == 6 {
+				right = 5;
+			}
+			for vert in 0 .. self.size {
+				for j in 0 .. 2 {
+					let x: i32 = right - j;
+					let upward: bool = (right + 1) & 2 == 0;
+					let y: i32 = if upward { self.size - 1 - vert } else { vert };
+					if !isfunction[(y * self.size + x) as usize] && i < raw.len() * 8 {
+						if self.module(x, y) ^ mask_pattern(self.mask, x, y) {
+							raw[i >> 3] |= 1 << (7 - (i & 7));
+						}
+						i += 1;
+					}
+				}
+			}
+			right -= 2;
+		}
+		
+		// Undo interleave_blocks(), keeping the data part of each block
+		let info: BlockInfo = self.block_structure();
+		let shortblocklen: usize = info.short_block_len;
+		let mut blocks: Vec<Vec<u8>> = vec![Vec::with_capacity(shortblocklen + 1); info.num_blocks];
+		let mut k: usize = 0;
+		for i in 0 ..= shortblocklen {
+			for (j, block) in blocks.iter_mut().enumerate() {
+				if i != shortblocklen - info.ecc_codewords_per_block || j >= info.num_short_blocks {
+					block.push(raw[k]);
+					k += 1;
+				}
+			}
+		}
+		blocks
+	}
+	
+	/// Returns the segments encoded in this QR Code, parsed from its data codewords.
+	/// 
+	/// Parsing stops at the terminator, at a mode indicator other than numeric,
+	/// alphanumeric, byte, kanji or ECI, or at a segment cut off by the end of the data.
+	/// Re-encoding the result with `encode_segments()` reproduces the same content,
+	/// e.g. at another ECC level.
+	pub fn segments(&self) -> Vec<QrSegment> {
+		let bits: Vec<bool> = self.data_codewords().iter()
+			.flat_map(|&b| (0 .. 8).rev().map(move |i| get_bit(u32::from(b), i)))
+			.collect();
+		let mut pos: usize = 0;
+		let mut read = |n: usize| -> Option<Vec<bool>> {
+			let field: &[bool] = bits.get(pos .. pos + n)?;
+			pos += n;
+			Some(field.to_vec())
+		};
+		let value = |field: &[bool]| field.iter().fold(0usize, |acc, &b| acc << 1 | usize::from(b));
+		
+		let mut result = Vec::new();
+		while let Some(indicator) = read(4) {
+			let mode: QrSegmentMode = match value(&indicator) {
+				0b0001 => QrSegmentMode::Numeric,
+				0b0010 => QrSegmentMode::Alphanumeric,
+				0b0100 => QrSegmentMode::Byte,
+				0b1000 => QrSegmentMode::Kanji,
+				0b0111 => QrSegmentMode::Eci,
+				_ => break,
+			};
+			if mode == QrSegmentMode::Eci {
+				// The assignment value takes 1, 2 or 3 bytes, signalled by its leading bits
+				let Some(mut data) = read(8) else { break };
+				let extra: usize = match (data[0], data[1], data[2]) {
+					(false, _, _) => 0,
+					(true, false, _) => 8,
+					(true, true, false) => 16,
+					_ => break,
+				};
+				let Some(rest) = read(extra) else { break };
+				data.extend(rest);
+				result.push(QrSegment::new(mode, 0, data));
+				continue;
+			}
+			let Some(count) = read(usize::from(mode.num_char_count_bits(self.version))) else { break };
+			let numchars: usize = value(&count);
+			let datalen: usize = match mode {
+				QrSegmentMode::Numeric => numchars / 3 * 10 + [0, 4, 7][numchars % 3],
+				QrSegmentMode::Alphanumeric => numchars / 2 * 11 + numchars % 2 * 6,
+				QrSegmentMode::Byte => numchars * 8,
+				_ => numchars * 13,
+			};
+			let Some(data) = read(datalen) else { break };
+			result.push(QrSegment::new(mode, numchars, data));
+		}
+		result
+	}
+	
+	/// Calls `f(x, y)` for each dark module, in row-major order, with the coordinates
+	/// shifted by `border` (the quiet zone width in modules).
+	/// 
+	/// This lets a renderer draw straight from the symbol without an intermediate buffer.
+	/// 
+	/// # Example
+	/// 
+	/// ```rust
+	/// use qrcode_lib::{QrCode, QrCodeEcc};
+	/// 
+	/// let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+	/// let mut path = String::new();
+	/// qr.for_each_dark_module(4, |x, y| path.push_str(&format!("M{},{}h1v1h-1z", x, y)));
+	/// assert!(path.starts_with("M4,4h1v1h-1z"));
+	/// ```
+	pub fn for_each_dark_module<F: FnMut(i32,i32)>(&self, border: i32, mut f: F) {
+		for y in 0 .. self.size {
+			for x in 0 .. self.size {
+				if self.module(x, y) {
+					f(x + border, y + border);
+				}
+			}
+		}
+	}
+	
+	/// Returns a copy of this QR Code with the modules at the given coordinates toggled,
+	/// e.g. to produce damaged samples for testing a decoder's error correction.
+	/// 
+	/// Only data and ECC modules can be flipped; a coordinate listed twice is toggled twice.
+	/// Returns `Err` naming the first coordinate that is out of bounds or of a function module.
+	pub fn with_flipped_modules(&self, coords: &[(i32,i32)]) -> Result<Self,QrError> {
+		let function: Vec<bool> = self.function_modules();
+		let mut result: QrCode = self.clone();
+		for &(x, y) in coords {
+			if !(0 .. self.size).contains(&x) || !(0 .. self.size).contains(&y)
+					|| function[(y * self.size + x) as usize] {
+				return Err(QrError::InvalidModule(x, y));
+			}
+			let module: &mut bool = result.module_mut(x, y);
+			*module = !*module;
+		}
+		Ok(result)
+	}
+	
+	/// Returns a copy of this QR Code with the given mask pattern instead of its own.
+	/// 
+	/// Only the masking and the format information are redone; the data and error
+	/// correction codewords are not encoded again, so this is cheap enough for tools
+	/// that compare the eight masks of one code. The penalty-based mask choice is not applied.
+	pub fn clone_with_mask(&self, mask: Mask) -> Self {
+		let mut result: QrCode = self.clone();
+		result.isfunction = self.function_modules();  // Not retained after encoding
+		result.apply_mask(self.mask);  // Undoes the current mask due to XOR
+		result.apply_mask(mask);
+		result.draw_format_bits(mask);
+		result.mask = mask;
+		result.isfunction.clear();
+		result.isfunction.shrink_to_fit();
+		result
+	}
+	
+	/// Checks that the given module matrix is structurally sound for the given version, without decoding it.
+	/// 
+	/// The matrix is indexed as `matrix[y][x]` with `true` for dark, as returned by `to_matrix()`.
+	/// This verifies the finder patterns and their light separators, the timing patterns, the
+	/// dark module, and that the format information (and for version 7 and up, the version
+	/// information) is within correctable distance of a valid BCH codeword in at least one copy.
+	/// 
+	/// With `auto_invert`, a matrix whose finder patterns match better with light and dark
+	/// swapped (as some scanners deliver them) is checked as if inverted.
+	/// 
+	/// Returns `Err` describing the first problem found.
+	pub fn validate_structure(matrix: &[Vec<bool>], version: Version, auto_invert: bool) -> Result<(), StructureError> {
+		let size: usize = version.module_count() as usize;
+		if matrix.len() != size || matrix.iter().any(|row| row.len() != size) {
+			return Err(StructureError::WrongSize(size));
+		}
+		let size = size as i32;
+		
+		// Modules of the finder patterns, including the separators, with their expected colors
+		let mut finders: Vec<(usize, i32, i32, bool)> = Vec::new();
+		for (i, &(cx, cy)) in [(3, 3), (size - 4, 3), (3, size - 4)].iter().enumerate() {
+			for dy in -4i32 ..= 4 {
+				for dx in -4i32 ..= 4 {
+					let (x, y) = (cx + dx, cy + dy);
+					if (0 .. size).contains(&x) && (0 .. size).contains(&y) {
+						let dist: i32 = std::cmp::max(dx.abs(), dy.abs());
+						finders.push((i, x, y, dist != 2 && dist != 4));
+					}
+				}
+			}
+		}
+		let invert: bool = auto_invert && finders.iter()
+			.filter(|&&(_, x, y, dark)| matrix[y as usize][x as usize] == dark).count() * 2 < finders.len();
+		let get = |x: i32, y: i32| -> bool { matrix[y as usize][x as usize] != invert };
+		
+		// Finder patterns, including the separators
+		for &(i, x, y, dark) in &finders {
+			if get(x, y) != dark {
+				return Err(StructureError::FinderPattern(i));
+			}
+		}
+		
+		// Timing patterns
+		for i in 8 .. size - 8 {
+			if get(6, i) != (i % 2 == 0) || get(i, 6) != (i % 2 == 0) {
+				return Err(StructureError::TimingPattern);
+			}
+		}
+		
+		if !get(8, size - 8) {
+			return Err(StructureError::DarkModule);
+		}
+		
+		// Format information: read both copies, bit i at the positions draw_format_bits() uses
+		let mut first: u32 = 0;
+		let mut second: u32 = 0;
+		for i in 0 .. 15 {
+			let (x, y) = match i {
+				0 ..= 5 => (8, i),
+				6 => (8, 7),
+				7 => (8, 8),
+				8 => (7, 8),
+				_ => (14 - i, 8),
+			};
+			first |= u32::from(get(x, y)) << i;
+			let (x, y) = if i < 8 { (size - 1 - i, 8) } else { (8, size - 15 + i) };
+			second |= u32::from(get(x, y)) << i;
+		}
+		let correctable = |read: u32, valid: u32| (read ^ valid).count_ones() <= 3;
+		if !(0 .. 32).map(QrCode::format_codeword).any(|cw| correctable(first, cw) || correctable(second, cw)) {
+			return Err(StructureError::FormatInfo);
+		}
+		
+		// Version information
+		if version.value() >= 7 {
+			let expected: u32 = QrCode::version_codeword(version);
+			let mut first: u32 = 0;
+			let mut second: u32 = 0;
+			for i in 0 .. 18 {
+				let a: i32 = size - 11 + i % 3;
+				let b: i32 = i / 3;
+				first  |= u32::from(get(a, b)) << i;
+				second |= u32::from(get(b, a)) << i;
+			}
+			if !correctable(first, expected) && !correctable(second, expected) {
+				return Err(StructureError::VersionInfo);
+			}
+		}
+		Ok(())
+	}
+	
 	// Returns the color of the module at the given coordinates, which must be in bounds.
 	pub(crate) fn module(&self, x: i32, y: i32) -> bool {
 		self.modules[(y * self.size + x) as usize]
@@ -272,6 +806,23 @@ impl QrCode {
 	
 	/*---- Private helper methods for constructor: Drawing function modules ----*/
 	
+	// Returns which modules belong to function patterns (finders, separators, timing,
+	// alignment, format and version information), indexed as y * size + x. The map is
+	// not kept after construction, so it is recomputed from the version.
+	pub(crate) fn function_modules(&self) -> Vec<bool> {
+		let size = self.size as usize;
+		let mut blank = Self {
+			version: self.version,
+			size: self.size,
+			mask: self.mask,
+			errorcorrectionlevel: self.errorcorrectionlevel,
+			modules   : vec![false; size * size],
+			isfunction: vec![false; size * size],
+		};
+		blank.draw_function_patterns();
+		blank.isfunction
+	}
+	
 	fn draw_function_patterns(&mut self) {
 		// Draw horizontal and vertical timing patterns
 		let size: i32 = self.size;
@@ -290,7 +841,7 @@ impl QrCode {
 		let numalign: usize = alignpatpos.len();
 		for i in 0 .. numalign {
 			for j in 0 .. numalign {
-				if !(i == 0 && j == 0 || i == 0 && j == numalign - 1 || i == numalign - 1 && j == 0) {
+				if (i, j) != (0, 0) && (i, j) != (0, numalign - 1) && (i, j) != (numalign - 1, 0) {
 					self.draw_alignment_pattern(alignpatpos[i], alignpatpos[j]);
 				}
 			}
@@ -302,14 +853,7 @@ impl QrCode {
 	}
 	
 	fn draw_format_bits(&mut self, mask: Mask) {
-		let bits: u32 = {
-			let data = u32::from(self.errorcorrectionlevel.format_bits() << 3 | mask.value());
-			let mut rem: u32 = data;
-			for _ in 0 .. 10 {
-				rem = (rem << 1) ^ ((rem >> 9) * 0x537);
-			}
-			(data << 10 | rem) ^ 0x5412
-		};
+		let bits: u32 = QrCode::format_codeword(u32::from(self.errorcorrectionlevel.format_bits() << 3 | mask.value()));
 		debug_assert_eq!(bits >> 15, 0);
 		
 		// Draw first copy
@@ -339,14 +883,7 @@ impl QrCode {
 			return;
 		}
 		
-		let bits: u32 = {
-			let data = u32::from(self.version.value());
-			let mut rem: u32 = data;
-			for _ in 0 .. 12 {
-				rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
-			}
-			data << 12 | rem
-		};
+		let bits: u32 = QrCode::version_codeword(self.version);
 		debug_assert_eq!(bits >> 18, 0);
 		
 		for i in 0 .. 18 {
@@ -358,6 +895,25 @@ impl QrCode {
 		}
 	}
 	
+	// Returns the masked 15-bit format codeword for the given 5-bit format data (ECC level and mask).
+	fn format_codeword(data: u32) -> u32 {
+		let mut rem: u32 = data;
+		for _ in 0 .. 10 {
+			rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+		}
+		(data << 10 | rem) ^ 0x5412
+	}
+	
+	// Returns the 18-bit version codeword for the given version.
+	fn version_codeword(ver: Version) -> u32 {
+		let data = u32::from(ver.value());
+		let mut rem: u32 = data;
+		for _ in 0 .. 12 {
+			rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+		}
+		data << 12 | rem
+	}
+	
 	fn draw_finder_pattern(&mut self, x: i32, y: i32) {
 		for dy in -4 ..= 4 {
 			for dx in -4 ..= 4 {
@@ -387,15 +943,20 @@ impl QrCode {
 	/*---- Private helper methods for constructor: Codewords and masking ----*/
 	
 	fn add_ecc_and_interleave(&self, data: &[u8]) -> Vec<u8> {
-		let ver: Version = self.version;
-		let ecl: QrCodeEcc = self.errorcorrectionlevel;
+		let blocks: Vec<Vec<u8>> = QrCode::make_ecc_blocks(data, self.version, self.errorcorrectionlevel);
+		QrCode::interleave_blocks(&blocks, QrCode::get_block_info(self.version, self.errorcorrectionlevel))
+	}
+	
+	// Splits the data into blocks and appends the ECC codewords to each. Short blocks get a
+	// padding byte after their data so that all blocks have the same length.
+	fn make_ecc_blocks(data: &[u8], ver: Version, ecl: QrCodeEcc) -> Vec<Vec<u8>> {
 		assert_eq!(data.len(), QrCode::get_num_data_codewords(ver, ecl), "Illegal argument");
 		
-		let numblocks: usize = QrCode::table_get(&NUM_ERROR_CORRECTION_BLOCKS, ver, ecl);
-		let blockecclen: usize = QrCode::table_get(&ECC_CODEWORDS_PER_BLOCK  , ver, ecl);
-		let rawcodewords: usize = QrCode::get_num_raw_data_modules(ver) / 8;
-		let numshortblocks: usize = numblocks - rawcodewords % numblocks;
-		let shortblocklen: usize = rawcodewords / numblocks;
+		let info: BlockInfo = QrCode::get_block_info(ver, ecl);
+		let numblocks: usize = info.num_blocks;
+		let blockecclen: usize = info.ecc_codewords_per_block;
+		let numshortblocks: usize = info.num_short_blocks;
+		let shortblocklen: usize = info.short_block_len;
 		
 		let mut blocks = Vec::<Vec<u8>>::with_capacity(numblocks);
 		let rsdiv: Vec<u8> = QrCode::reed_solomon_compute_divisor(blockecclen);
@@ -411,8 +972,16 @@ impl QrCode {
 			dat.extend_from_slice(&ecc);
 			blocks.push(dat);
 		}
+		blocks
+	}
+	
+	// Interleaves the codewords of padded blocks from make_ecc_blocks(), skipping the padding.
+	fn interleave_blocks(blocks: &[Vec<u8>], info: BlockInfo) -> Vec<u8> {
+		let blockecclen: usize = info.ecc_codewords_per_block;
+		let numshortblocks: usize = info.num_short_blocks;
+		let shortblocklen: usize = info.short_block_len;
 		
-		let mut result = Vec::<u8>::with_capacity(rawcodewords);
+		let mut result = Vec::<u8>::with_capacity(info.num_blocks * shortblocklen + info.num_blocks - numshortblocks);
 		for i in 0 ..= shortblocklen {
 			for (j, block) in blocks.iter().enumerate() {
 				if i != shortblocklen - blockecclen || j >= numshortblocks {
@@ -451,17 +1020,7 @@ impl QrCode {
 	fn apply_mask(&mut self, mask: Mask) {
 		for y in 0 .. self.size {
 			for x in 0 .. self.size {
-				let invert: bool = match mask.value() {
-					0 => (x + y) % 2 == 0,
-					1 => y % 2 == 0,
-					2 => x % 3 == 0,
-					3 => (x + y) % 3 == 0,
-					4 => (x / 3 + y / 2) % 2 == 0,
-					5 => x * y % 2 + x * y % 3 == 0,
-					6 => (x * y % 2 + x * y % 3) % 2 == 0,
-					7 => ((x + y) % 2 + x * y % 3) % 2 == 0,
-					_ => unreachable!(),
-				};
+				let invert: bool = mask_pattern(mask, x, y);
 				*self.module_mut(x, y) ^= invert & !self.isfunction[(y * self.size + x) as usize];
 			}
 		}
@@ -537,15 +1096,15 @@ impl QrCode {
 		let dark: i32 = self.modules.iter().copied().map(i32::from).sum();
 		let total: i32 = size * size;
 		let k: i32 = ((dark * 20 - total * 10).abs() + total - 1) / total - 1;
-		debug_assert!(0 <= k && k <= 9);
+		debug_assert!((0 ..= 9).contains(&k));
 		result += k * PENALTY_N4;
-		debug_assert!(0 <= result && result <= 2568888);
+		debug_assert!((0 ..= 2568888).contains(&result));
 		result
 	}
 	
 	/*---- Private helper functions ----*/
 	
-	fn get_alignment_pattern_positions(&self) -> Vec<i32> {
+	pub(crate) fn get_alignment_pattern_positions(&self) -> Vec<i32> {
 		let ver = i32::from(self.version.value());
 		if ver == 1 {
 			vec![]
@@ -560,8 +1119,8 @@ impl QrCode {
 		}
 	}
 	
-	fn get_num_raw_data_modules(ver: Version) -> usize {
-		let ver = usize::from(ver.value());
+	pub(crate) const fn get_num_raw_data_modules(ver: Version) -> usize {
+		let ver = ver.value() as usize;
 		let mut result: usize = (16 * ver + 128) * ver + 64;
 		if ver >= 2 {
 			let numalign: usize = ver / 7 + 2;
@@ -570,16 +1129,32 @@ impl QrCode {
 				result -= 36;
 			}
 		}
-		debug_assert!((208 ..= 29648).contains(&result));
+		debug_assert!(208 <= result && result <= 29648);
 		result
 	}
 	
-	fn get_num_data_codewords(ver: Version, ecl: QrCodeEcc) -> usize {
+	pub(crate) fn get_num_data_codewords(ver: Version, ecl: QrCodeEcc) -> usize {
 		QrCode::get_num_raw_data_modules(ver) / 8
 			- QrCode::table_get(&ECC_CODEWORDS_PER_BLOCK    , ver, ecl)
 			* QrCode::table_get(&NUM_ERROR_CORRECTION_BLOCKS, ver, ecl)
 	}
 	
+	pub(crate) fn get_block_info(ver: Version, ecl: QrCodeEcc) -> BlockInfo {
+		let numblocks: usize = QrCode::table_get(&NUM_ERROR_CORRECTION_BLOCKS, ver, ecl);
+		let blockecclen: usize = QrCode::table_get(&ECC_CODEWORDS_PER_BLOCK  , ver, ecl);
+		let rawcodewords: usize = QrCode::get_num_raw_data_modules(ver) / 8;
+		let shortblocklen: usize = rawcodewords / numblocks;
+		BlockInfo {
+			num_blocks: numblocks,
+			num_short_blocks: numblocks - rawcodewords % numblocks,
+			short_block_len: shortblocklen,
+			long_block_len: shortblocklen + 1,
+			short_block_data_codewords: shortblocklen - blockecclen,
+			long_block_data_codewords: shortblocklen - blockecclen + 1,
+			ecc_codewords_per_block: blockecclen,
+		}
+	}
+	
 	fn table_get(table: &'static [[i8; 41]; 4], ver: Version, ecl: QrCodeEcc) -> usize {
 		table[ecl.ordinal()][usize::from(ver.value())] as usize
 	}
@@ -624,20 +1199,96 @@ impl QrCode {
 	}
 }
 
-struct FinderPenalty {
+/// Returns whether the given mask pattern inverts the module at the given coordinates.
+/// 
+/// The coordinates must be non-negative, with (0, 0) at the top left. This is the raw
+/// pattern formula from the standard; function modules are excluded by the caller.
+/// Applying a mask to a whole matrix amounts to XORing every data module with this value.
+pub fn mask_pattern(mask: Mask, x: i32, y: i32) -> bool {
+	match mask.value() {
+		0 => (x + y) % 2 == 0,
+		1 => y % 2 == 0,
+		2 => x % 3 == 0,
+		3 => (x + y) % 3 == 0,
+		4 => (x / 3 + y / 2) % 2 == 0,
+		5 => x * y % 2 + x * y % 3 == 0,
+		6 => (x * y % 2 + x * y % 3) % 2 == 0,
+		7 => ((x + y) % 2 + x * y % 3) % 2 == 0,
+		_ => unreachable!(),
+	}
+}
+
+/// A payload that can be encoded as a QR Code: text (`&str`) or binary data (`&[u8]`).
+/// 
+/// This allows code that is generic over the payload type; see `QrCode::encode()`.
+pub trait Encodable {
+	/// Returns a QR Code representing this payload at the given error correction level,
+	/// as `QrCode::encode_text()` or `QrCode::encode_binary()` would.
+	fn encode(&self, ecl: QrCodeEcc) -> Result<QrCode,DataTooLong>;
+}
+
+impl Encodable for &str {
+	fn encode(&self, ecl: QrCodeEcc) -> Result<QrCode,DataTooLong> {
+		QrCode::encode_text(self, ecl)
+	}
+}
+
+impl Encodable for &[u8] {
+	fn encode(&self, ecl: QrCodeEcc) -> Result<QrCode,DataTooLong> {
+		QrCode::encode_binary(self, ecl)
+	}
+}
+
+/// The error correction block layout of a QR Code at a given version and ECC level.
+/// 
+/// The raw codewords are divided into short blocks followed by long blocks, where each
+/// long block holds one more data codeword. All blocks carry the same number of ECC codewords.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BlockInfo {
+	/// The total number of error correction blocks.
+	pub num_blocks: usize,
+	/// The number of short blocks; the remaining blocks are long blocks.
+	pub num_short_blocks: usize,
+	/// The number of codewords (data plus ECC) in each short block.
+	pub short_block_len: usize,
+	/// The number of codewords (data plus ECC) in each long block.
+	pub long_block_len: usize,
+	/// The number of data codewords in each short block.
+	pub short_block_data_codewords: usize,
+	/// The number of data codewords in each long block.
+	pub long_block_data_codewords: usize,
+	/// The number of error correction codewords in every block.
+	pub ecc_codewords_per_block: usize,
+}
+
+/// The blocks and interleaved codewords of a QR Code, as returned by `QrCode::interleave_debug()`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InterleaveDebug {
+	/// The data codewords of each block, short blocks first.
+	pub data_blocks: Vec<Vec<u8>>,
+	/// The error correction codewords of each block, in the same order.
+	pub ecc_blocks: Vec<Vec<u8>>,
+	/// The final codeword sequence: the data codewords interleaved across blocks,
+	/// followed by the ECC codewords interleaved across blocks.
+	pub interleaved: Vec<u8>,
+}
+
+// Tracks the run lengths of one row or column to detect finder-like 1:1:3:1:1 patterns
+// (dark:light:dark:light:dark) with at least 4 light modules on either side.
+pub(crate) struct FinderPenalty {
 	qr_size: i32,
 	run_history: [i32; 7],
 }
 
 impl FinderPenalty {
-	pub fn new(size: i32) -> Self {
+	pub(crate) fn new(size: i32) -> Self {
 		Self {
 			qr_size: size,
 			run_history: [0i32; 7],
 		}
 	}
 	
-	pub fn add_history(&mut self, mut currentrunlength: i32) {
+	pub(crate) fn add_history(&mut self, mut currentrunlength: i32) {
 		if self.run_history[0] == 0 {
 			currentrunlength += self.qr_size;
 		}
@@ -648,7 +1299,7 @@ impl FinderPenalty {
 		rh[0] = currentrunlength;
 	}
 	
-	pub fn count_patterns(&self) -> i32 {
+	pub(crate) fn count_patterns(&self) -> i32 {
 		let rh = &self.run_history;
 		let n = rh[1];
 		debug_assert!(n <= self.qr_size * 3);
@@ -658,7 +1309,7 @@ impl FinderPenalty {
 		+ i32::from(core && rh[6] >= n * 4 && rh[0] >= n))
 	}
 	
-	pub fn terminate_and_count(mut self, currentruncolor: bool, mut currentrunlength: i32) -> i32 {
+	pub(crate) fn terminate_and_count(mut self, currentruncolor: bool, mut currentrunlength: i32) -> i32 {
 		if currentruncolor {
 			self.add_history(currentrunlength);
 			currentrunlength = 0;
@@ -669,6 +1320,31 @@ impl FinderPenalty {
 	}
 }
 
+/// The number of data bits (excluding error correction) of each version at each ECC level.
+/// 
+/// Indexed as `CAPACITY_BITS[ecl][version]`, with the ECC levels in the order Low, Medium,
+/// Quartile, High. Index 0 is not a version and holds 0. The usable payload is smaller,
+/// since each segment also needs a mode indicator and a character count.
+pub const CAPACITY_BITS: [[usize; 41]; 4] = {
+	let mut result = [[0usize; 41]; 4];
+	let mut ecl: usize = 0;
+	while ecl < 4 {
+		let mut ver: u8 = 1;
+		while ver <= 40 {
+			let v: usize = ver as usize;
+			let ecccodewords: usize = ECC_CODEWORDS_PER_BLOCK[ecl][v] as usize * NUM_ERROR_CORRECTION_BLOCKS[ecl][v] as usize;
+			result[ecl][v] = (QrCode::get_num_raw_data_modules(Version::new(ver)) / 8 - ecccodewords) * 8;
+			ver += 1;
+		}
+		ecl += 1;
+	}
+	result
+};
+
+// All mask patterns, the candidates of automatic mask selection.
+const ALL_MASKS: [Mask; 8] = [Mask::new(0), Mask::new(1), Mask::new(2), Mask::new(3),
+	Mask::new(4), Mask::new(5), Mask::new(6), Mask::new(7)];
+
 const PENALTY_N1: i32 =  3;
 const PENALTY_N2: i32 =  3;
 const PENALTY_N3: i32 = 40;
@@ -688,3 +1364,525 @@ static NUM_ERROR_CORRECTION_BLOCKS: [[i8; 41]; 4] = [
 	[-1, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32, 35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81],
 ];
 
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn test_block_structure_5q() {
+		let segs = QrSegment::make_segments("HELLO");
+		let qr = QrCode::encode_segments_advanced(&segs, QrCodeEcc::Quartile,
+			Version::new(5), Version::new(5), None, false).unwrap();
+		let info = qr.block_structure();
+		assert_eq!(info.num_blocks, 4);
+		assert_eq!(info.num_short_blocks, 2);
+		assert_eq!(info.short_block_data_codewords, 15);
+		assert_eq!(info.long_block_data_codewords, 16);
+		assert_eq!(info.ecc_codewords_per_block, 18);
+		assert_eq!(info.short_block_len, 33);
+		assert_eq!(info.long_block_len, 34);
+	}
+	
+	#[test]
+	fn test_fits_text_at_max_capacity() {
+		let full = "a".repeat(2953);
+		assert!(QrCode::fits_text(&full, QrCodeEcc::Low));
+		assert_eq!(QrCode::remaining_capacity_after(&full, QrCodeEcc::Low), Some(0));
+		assert_eq!(QrCode::encode_text(&full, QrCodeEcc::Low).unwrap().version(), Version::MAX);
+		
+		let over = "a".repeat(2954);
+		assert!(!QrCode::fits_text(&over, QrCodeEcc::Low));
+		assert_eq!(QrCode::remaining_capacity_after(&over, QrCodeEcc::Low), None);
+		assert!(QrCode::encode_text(&over, QrCodeEcc::Low).is_err());
+		
+		assert_eq!(QrCode::remaining_capacity_after("a", QrCodeEcc::Low), Some(2952));
+	}
+	
+	#[test]
+	fn test_validate_structure() {
+		for text in ["Hello", &"x".repeat(300)] {
+			let qr = QrCode::encode_text(text, QrCodeEcc::Medium).unwrap();
+			assert_eq!(QrCode::validate_structure(&qr.to_matrix(), qr.version(), false), Ok(()));
+		}
+		
+		let qr = QrCode::encode_text("Hello", QrCodeEcc::Medium).unwrap();
+		let mut matrix = qr.to_matrix();
+		matrix[6][10] = !matrix[6][10];
+		assert_eq!(QrCode::validate_structure(&matrix, qr.version(), false), Err(StructureError::TimingPattern));
+		
+		let mut matrix = qr.to_matrix();
+		matrix[2][2] = !matrix[2][2];
+		assert_eq!(QrCode::validate_structure(&matrix, qr.version(), false), Err(StructureError::FinderPattern(0)));
+		
+		assert_eq!(QrCode::validate_structure(&qr.to_matrix(), Version::new(2), false), Err(StructureError::WrongSize(25)));
+		
+		// An inverted matrix passes only when inversion is detected
+		let qr = QrCode::encode_text("Inverted scan", QrCodeEcc::Quartile).unwrap();
+		let inverted: Vec<Vec<bool>> = qr.to_matrix().iter().map(|row| row.iter().map(|&m| !m).collect()).collect();
+		assert_eq!(QrCode::validate_structure(&inverted, qr.version(), false), Err(StructureError::FinderPattern(0)));
+		assert_eq!(QrCode::validate_structure(&inverted, qr.version(), true), Ok(()));
+		assert_eq!(QrCode::validate_structure(&qr.to_matrix(), qr.version(), true), Ok(()));
+	}
+	
+	#[test]
+	fn test_mask_pattern_0() {
+		for y in 0 .. 21 {
+			for x in 0 .. 21 {
+				assert_eq!(mask_pattern(Mask::new(0), x, y), (x + y) % 2 == 0);
+			}
+		}
+	}
+	
+	#[test]
+	fn test_encode_text_explained() {
+		let (qr, segs) = QrCode::encode_text_explained("ABC123", QrCodeEcc::Low).unwrap();
+		assert_eq!(segs, vec![(QrSegmentMode::Alphanumeric, 6)]);
+		assert!(qr == QrCode::encode_text("ABC123", QrCodeEcc::Low).unwrap());
+		
+		let (_, segs) = QrCode::encode_text_explained("Hello, World", QrCodeEcc::Low).unwrap();
+		assert_eq!(segs, vec![(QrSegmentMode::Byte, 12)]);
+	}
+	
+	#[test]
+	fn test_get_module_checked() {
+		let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+		let size = qr.size();
+		assert_eq!(qr.get_module_checked(0, 0), Some(true));
+		assert_eq!(qr.get_module_checked(size - 1, size - 1), Some(qr.get_module(size - 1, size - 1)));
+		assert_eq!(qr.get_module_checked(size, 0), None);
+		assert_eq!(qr.get_module_checked(0, size), None);
+		assert_eq!(qr.get_module_checked(-1, 0), None);
+	}
+	
+	#[test]
+	fn test_encode_text_min_version() {
+		let qr = QrCode::encode_text_min_version("Hi", QrCodeEcc::Low, Version::new(10)).unwrap();
+		assert_eq!(qr.version(), Version::new(10));
+		let long = "a".repeat(500);
+		let qr = QrCode::encode_text_min_version(&long, QrCodeEcc::Low, Version::new(2)).unwrap();
+		assert_eq!(qr.version(), QrCode::encode_text(&long, QrCodeEcc::Low).unwrap().version());
+	}
+	
+	#[test]
+	fn test_function_modules() {
+		let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+		let size = qr.size() as usize;
+		let map = qr.function_modules();
+		assert_eq!(map.len(), size * size);
+		// Finder with separator, timing and the dark module are function modules; the
+		// bottom right corner holds data
+		assert!(map[0] && map[7 * size + 7] && map[6 * size + 10] && map[(size - 8) * size + 8]);
+		assert!(!map[size * size - 1]);
+		// Version 1: 3 * 64 finder/separator + 2 * 5 timing + 31 format/dark module
+		assert_eq!(map.iter().filter(|&&f| f).count(), 3 * 64 + 10 + 31);
+	}
+	
+	#[test]
+	fn test_all_masks() {
+		let segs = QrSegment::make_segments("Masking demo");
+		let codes = QrCode::all_masks(&segs, QrCodeEcc::Medium, Version::new(2)).unwrap();
+		let size = codes[0].size();
+		let function = codes[0].function_modules();
+		for (i, a) in codes.iter().enumerate() {
+			assert_eq!(a.mask(), Mask::new(i as u8));
+			assert_eq!(a.version(), Version::new(2));
+			for b in &codes[i + 1 ..] {
+				let mut data_differs = false;
+				for y in 0 .. size {
+					for x in 0 .. size {
+						let same = a.get_module(x, y) == b.get_module(x, y);
+						if !function[(y * size + x) as usize] {
+							data_differs |= !same;
+						} else if x != 8 && y != 8 {
+							// Everything but the format information is shared
+							assert!(same, "function module ({}, {}) differs", x, y);
+						}
+					}
+				}
+				assert!(data_differs);
+			}
+		}
+		assert!(QrCode::all_masks(&QrSegment::make_segments(&"x".repeat(100)), QrCodeEcc::High, Version::new(2)).is_err());
+	}
+	
+	#[test]
+	fn test_remaining_capacity_counts_utf8_bytes() {
+		let emoji: String = "\u{1F600}".repeat(10);
+		assert_eq!(emoji.chars().count(), 10);
+		assert_eq!(emoji.len(), 40);
+		// 2953 bytes fit at version 40-L, so 40 bytes of emoji leave 2913, not 2943
+		assert_eq!(QrCode::remaining_capacity_after(&emoji, QrCodeEcc::Low), Some(2953 - 40));
+		
+		let fits: String = "\u{1F600}".repeat(738);
+		assert!(QrCode::fits_text(&fits, QrCodeEcc::Low));
+		assert!(!QrCode::fits_text(&(fits + "\u{1F600}"), QrCodeEcc::Low));
+	}
+	
+	#[test]
+	fn test_interleave_debug_5q() {
+		let ver = Version::new(5);
+		let ecl = QrCodeEcc::Quartile;
+		let data: Vec<u8> = (0 .. QrCode::get_num_data_codewords(ver, ecl)).map(|i| (i * 7) as u8).collect();
+		let dbg: InterleaveDebug = QrCode::interleave_debug(&data, ver, ecl);
+		assert_eq!(dbg.data_blocks.len(), 4);
+		assert_eq!(dbg.data_blocks.iter().map(Vec::len).collect::<Vec<_>>(), vec![15, 15, 16, 16]);
+		assert!(dbg.ecc_blocks.iter().all(|b| b.len() == 18));
+		assert_eq!(dbg.data_blocks.concat(), data);
+		assert_eq!(dbg.interleaved.len(), QrCode::get_num_raw_data_modules(ver) / 8);
+		
+		// De-interleave: take codewords round-robin from each block that still has room
+		let mut datablocks = vec![Vec::new(); 4];
+		let mut eccblocks = vec![Vec::new(); 4];
+		let mut iter = dbg.interleaved.iter().copied();
+		for i in 0 .. 16 {
+			for (j, block) in datablocks.iter_mut().enumerate() {
+				if i < dbg.data_blocks[j].len() {
+					block.push(iter.next().unwrap());
+				}
+			}
+		}
+		for _ in 0 .. 18 {
+			for block in eccblocks.iter_mut() {
+				block.push(iter.next().unwrap());
+			}
+		}
+		assert!(iter.next().is_none());
+		assert_eq!(datablocks, dbg.data_blocks);
+		assert_eq!(eccblocks, dbg.ecc_blocks);
+	}
+	
+	#[test]
+	fn test_encode_binary_eci() {
+		let chunks: [(u32, &[u8]); 2] = [(3, b"Caf\xE9 "), (26, "Grüße".as_bytes())];
+		let qr = QrCode::encode_binary_eci(&chunks, QrCodeEcc::Medium).unwrap();
+		let expect = QrCode::encode_segments(&QrSegment::make_eci_bytes(&chunks), QrCodeEcc::Medium).unwrap();
+		assert_eq!(qr.to_matrix(), expect.to_matrix());
+		assert_ne!(qr.to_matrix(), QrCode::encode_binary(&[b"Caf\xE9 " as &[u8], "Grüße".as_bytes()].concat(), QrCodeEcc::Medium).unwrap().to_matrix());
+	}
+	
+	#[test]
+	fn test_with_flipped_modules() {
+		let qr = QrCode::encode_text("Damaged sample", QrCodeEcc::High).unwrap();
+		let function: Vec<bool> = qr.function_modules();
+		let size: i32 = qr.size();
+		// A handful of data modules, far fewer than High ECC can correct
+		let coords: Vec<(i32,i32)> = (0 .. size * size)
+			.filter(|&i| !function[i as usize])
+			.step_by(17)
+			.take(8)
+			.map(|i| (i % size, i / size))
+			.collect();
+		let damaged = qr.with_flipped_modules(&coords).unwrap();
+		// Every block stays within what its ECC codewords can correct
+		let budget: usize = qr.block_structure().ecc_codewords_per_block / 2;
+		for (before, after) in qr.codeword_blocks().iter().zip(damaged.codeword_blocks()) {
+			let errors: usize = before.iter().zip(&after).filter(|(a, b)| a != b).count();
+			assert!(errors <= budget, "{} codeword errors, budget {}", errors, budget);
+		}
+		let dbg: InterleaveDebug = QrCode::interleave_debug(&qr.data_codewords(), qr.version(), qr.error_correction_level());
+		let expected: Vec<Vec<u8>> = dbg.data_blocks.iter().zip(&dbg.ecc_blocks).map(|(d, e)| [d.as_slice(), e.as_slice()].concat()).collect();
+		assert_eq!(qr.codeword_blocks(), expected);
+		assert!(damaged.codeword_blocks() != qr.codeword_blocks());
+		for y in 0 .. size {
+			for x in 0 .. size {
+				assert_eq!(damaged.get_module(x, y) != qr.get_module(x, y), coords.contains(&(x, y)));
+			}
+		}
+		
+		assert!(matches!(qr.with_flipped_modules(&[(0, 0)]), Err(QrError::InvalidModule(0, 0))));
+		assert!(matches!(qr.with_flipped_modules(&[(size, 0)]), Err(QrError::InvalidModule(_, 0))));
+	}
+	
+	#[test]
+	fn test_encode_generic() {
+		fn encode_low<E: Encodable>(data: E) -> QrCode {
+			QrCode::encode(data, QrCodeEcc::Low).unwrap()
+		}
+		let text: &str = "HELLO 123";
+		let bytes: &[u8] = b"\x00\xFFbinary";
+		assert!(encode_low(text) == QrCode::encode_text(text, QrCodeEcc::Low).unwrap());
+		assert!(encode_low(bytes) == QrCode::encode_binary(bytes, QrCodeEcc::Low).unwrap());
+	}
+	
+	// Scans a line given as (color, length) runs exactly as get_penalty_score() does,
+	// returning the number of finder-like patterns found.
+	fn count_finder_patterns(runs: &[(bool, i32)]) -> i32 {
+		let line: Vec<bool> = runs.iter().flat_map(|&(color, len)| std::iter::repeat_n(color, len as usize)).collect();
+		let mut runhistory = FinderPenalty::new(line.len() as i32);
+		let mut result: i32 = 0;
+		let mut runcolor = false;
+		let mut runx: i32 = 0;
+		for &color in &line {
+			if color == runcolor {
+				runx += 1;
+			} else {
+				runhistory.add_history(runx);
+				if !runcolor {
+					result += runhistory.count_patterns();
+				}
+				runcolor = color;
+				runx = 1;
+			}
+		}
+		result + runhistory.terminate_and_count(runcolor, runx)
+	}
+	
+	#[test]
+	fn test_finder_penalty_patterns() {
+		const D: bool = true;
+		const L: bool = false;
+		// 1:1:3:1:1 with 4 light modules on both sides counts once per side
+		assert_eq!(count_finder_patterns(&[(L, 4), (D, 1), (L, 1), (D, 3), (L, 1), (D, 1), (L, 4), (D, 6)]), 2);
+		// Only one side has 4 light modules
+		assert_eq!(count_finder_patterns(&[(D, 2), (L, 3), (D, 1), (L, 1), (D, 3), (L, 1), (D, 1), (L, 4), (D, 2)]), 1);
+		// Scaled 2:2:6:2:2 needs 8 light modules on a side
+		assert_eq!(count_finder_patterns(&[(L, 8), (D, 2), (L, 2), (D, 6), (L, 2), (D, 2), (L, 8)]), 2);
+		assert_eq!(count_finder_patterns(&[(D, 1), (L, 4), (D, 2), (L, 2), (D, 6), (L, 2), (D, 2), (L, 4), (D, 1)]), 0);
+		// Wrong ratios
+		assert_eq!(count_finder_patterns(&[(L, 4), (D, 1), (L, 1), (D, 2), (L, 1), (D, 1), (L, 4)]), 0);
+		assert_eq!(count_finder_patterns(&[(L, 4), (D, 1), (L, 2), (D, 3), (L, 1), (D, 1), (L, 4)]), 0);
+	}
+	
+	#[test]
+	fn test_finder_penalty_at_row_boundary() {
+		const D: bool = true;
+		const L: bool = false;
+		// The light border beyond the row's ends counts as light modules
+		assert_eq!(count_finder_patterns(&[(D, 1), (L, 1), (D, 3), (L, 1), (D, 1), (L, 4), (D, 3)]), 2);
+		assert_eq!(count_finder_patterns(&[(D, 3), (L, 4), (D, 1), (L, 1), (D, 3), (L, 1), (D, 1)]), 2);
+		assert_eq!(count_finder_patterns(&[(D, 1), (L, 1), (D, 3), (L, 1), (D, 1)]), 2);
+		
+		// Driving the history directly: the first run gets the border added
+		let mut fp = FinderPenalty::new(21);
+		for len in [0, 1, 1, 3, 1, 1] {
+			fp.add_history(len);
+		}
+		assert_eq!(fp.run_history, [1, 1, 3, 1, 1, 21, 0]);
+		assert_eq!(fp.terminate_and_count(false, 4), 2);
+	}
+	
+	#[test]
+	fn test_capacity_bits() {
+		assert_eq!(Version::all().count(), 40);
+		assert_eq!(Version::all().next(), Some(Version::MIN));
+		assert_eq!(Version::all().last(), Some(Version::MAX));
+		for ecl in [QrCodeEcc::Low, QrCodeEcc::Medium, QrCodeEcc::Quartile, QrCodeEcc::High] {
+			let row: &[usize; 41] = &CAPACITY_BITS[ecl.ordinal()];
+			assert_eq!(row[0], 0);
+			for ver in Version::all() {
+				assert_eq!(row[usize::from(ver.value())], ver.data_codewords(ecl) * 8);
+			}
+			assert!(row[1 ..].windows(2).all(|w| w[0] < w[1]));
+		}
+		assert_eq!(CAPACITY_BITS[0][40], 2956 * 8);
+	}
+	
+	#[test]
+	fn test_smart_encode() {
+		for text in ["Invoice #2024-000123456789, total $1,234.56", "https://example.com/p/9876543210987654321098765432109876543210", "hello"] {
+			let smart = QrCode::smart_encode(text, QrCodeEcc::Medium).unwrap();
+			let plain = QrCode::encode_text(text, QrCodeEcc::Medium).unwrap();
+			assert!(smart.version() <= plain.version(), "{:?}", text);
+		}
+		// The digit run in the URL saves enough to drop a version
+		let text = "https://example.com/p/9876543210987654321098765432109876543210";
+		assert!(QrCode::smart_encode(text, QrCodeEcc::Medium).unwrap().version()
+			< QrCode::encode_text(text, QrCodeEcc::Medium).unwrap().version());
+		assert!(QrCode::smart_encode(&"9".repeat(8000), QrCodeEcc::Low).is_err());
+	}
+	
+	#[test]
+	fn test_max_erasable_modules() {
+		let segs = QrSegment::make_segments("BUDGET");
+		let budget = |ecl: QrCodeEcc| QrCode::encode_segments_advanced(&segs, ecl,
+			Version::new(5), Version::new(5), None, false).unwrap().max_erasable_modules();
+		assert!(budget(QrCodeEcc::High) > budget(QrCodeEcc::Quartile));
+		assert!(budget(QrCodeEcc::Quartile) > budget(QrCodeEcc::Medium));
+		assert!(budget(QrCodeEcc::Medium) > budget(QrCodeEcc::Low));
+		// Version 5-H: 4 blocks of 22 ECC codewords, each correcting 11
+		assert_eq!(budget(QrCodeEcc::High), 4 * 11 * 8);
+	}
+	
+	#[test]
+	fn test_clone_with_mask() {
+		let segs = QrSegment::make_segments("Re-mask me");
+		let qr = QrCode::encode_segments(&segs, QrCodeEcc::Medium).unwrap();
+		assert!(qr.clone_with_mask(qr.mask()) == qr);
+		
+		let function: Vec<bool> = qr.function_modules();
+		for i in 0u8 .. 8 {
+			let mask = Mask::new(i);
+			let remasked = qr.clone_with_mask(mask);
+			// Same as encoding with that mask forced
+			let forced = QrCode::encode_segments_advanced(&segs, qr.error_correction_level(),
+				qr.version(), qr.version(), Some(mask), false).unwrap();
+			assert!(remasked == forced);
+			assert_eq!(remasked.mask(), mask);
+			if mask == qr.mask() {
+				continue;
+			}
+			// Apart from the format information, only non-function modules change
+			let mut changed_data = false;
+			for y in 0 .. qr.size() {
+				for x in 0 .. qr.size() {
+					if remasked.get_module(x, y) != qr.get_module(x, y) {
+						let is_format: bool = (x == 8 && (y <= 8 || y >= qr.size() - 8)) || (y == 8 && (x <= 8 || x >= qr.size() - 8));
+						assert!(!function[(y * qr.size() + x) as usize] || is_format, "({}, {})", x, y);
+						changed_data |= !function[(y * qr.size() + x) as usize];
+					}
+				}
+			}
+			assert!(changed_data);
+		}
+	}
+	
+	#[test]
+	fn test_encode_text_strict() {
+		assert!(matches!(QrCode::encode_text_strict("https://x.com ", QrCodeEcc::Low), Err(QrError::SurroundingWhitespace(' '))));
+		assert!(matches!(QrCode::encode_text_strict("\thttps://x.com", QrCodeEcc::Low), Err(QrError::SurroundingWhitespace('\t'))));
+		assert!(matches!(QrCode::encode_text_strict("https://x.com/\u{7}", QrCodeEcc::Low), Err(QrError::NonPrintableCharacter('\u{7}'))));
+		assert!(matches!(QrCode::encode_text_strict("https://x.com/a\tb", QrCodeEcc::Low), Err(QrError::NonPrintableCharacter('\t'))));
+		
+		// Inner spaces and line breaks are fine
+		for text in ["https://x.com", "HELLO WORLD", "line 1\r\nline 2"] {
+			assert!(QrCode::encode_text_strict(text, QrCodeEcc::Low).unwrap() == QrCode::encode_text(text, QrCodeEcc::Low).unwrap());
+		}
+		assert!(matches!(QrCode::encode_text_strict(&"a".repeat(3000), QrCodeEcc::Low), Err(QrError::DataTooLong(_))));
+	}
+	
+	#[test]
+	fn test_for_each_dark_module() {
+		let qr = QrCode::encode_text("Stream me", QrCodeEcc::Medium).unwrap();
+		let mut seen = std::collections::HashSet::new();
+		qr.for_each_dark_module(2, |x, y| assert!(seen.insert((x, y))));
+		let expected: std::collections::HashSet<(i32,i32)> = qr.to_matrix().iter().enumerate()
+			.flat_map(|(y, row)| row.iter().enumerate().filter(|(_, &dark)| dark)
+				.map(move |(x, _)| (x as i32 + 2, y as i32 + 2)))
+			.collect();
+		assert_eq!(seen, expected);
+	}
+	
+	#[test]
+	fn test_encode_segments_with_masks() {
+		let segs = QrSegment::make_segments("Hello");
+		let auto = QrCode::encode_segments(&segs, QrCodeEcc::Medium).unwrap();
+		assert_ne!(auto.mask(), Mask::new(2));
+		let restricted = QrCode::encode_segments_with_masks(&segs, QrCodeEcc::Medium,
+			Version::MIN, Version::MAX, &[Mask::new(2)], true).unwrap();
+		assert_eq!(restricted.mask(), Mask::new(2));
+		// The lowest-penalty mask wins when it is allowed
+		let among = QrCode::encode_segments_with_masks(&segs, QrCodeEcc::Medium,
+			Version::MIN, Version::MAX, &[Mask::new(2), auto.mask()], true).unwrap();
+		assert!(among == auto);
+		let all = QrCode::encode_segments_with_masks(&segs, QrCodeEcc::Medium,
+			Version::MIN, Version::MAX, &[], true).unwrap();
+		assert!(all == auto);
+	}
+	
+	#[test]
+	fn test_version_40_capacity_edges() {
+		// Byte mode: 2953 bytes fill version 40-L exactly, without terminator or pad bytes
+		let qr = QrCode::encode_binary(&[0xA5u8; 2953], QrCodeEcc::Low).unwrap();
+		assert_eq!((qr.version(), qr.error_correction_level()), (Version::MAX, QrCodeEcc::Low));
+		let Err(err) = QrCode::encode_binary(&[0xA5u8; 2954], QrCodeEcc::Low) else { panic!() };
+		assert!(matches!(err, DataTooLong::DataOverCapacity(23652, 23648)));
+		
+		// Numeric mode: 7089 digits leave 2 bits for a truncated terminator
+		let digits = "1234567890".repeat(709);
+		let qr = QrCode::encode_text(&digits[.. 7089], QrCodeEcc::Low).unwrap();
+		assert_eq!((qr.version(), qr.error_correction_level()), (Version::MAX, QrCodeEcc::Low));
+		assert!(QrCode::encode_text(&digits[.. 7090], QrCodeEcc::Low).is_err());
+		
+		// Alphanumeric mode: 4296 characters
+		let text = "AB1 ".repeat(1075);
+		assert_eq!(QrCode::encode_text(&text[.. 4296], QrCodeEcc::Low).unwrap().version(), Version::MAX);
+		assert!(QrCode::encode_text(&text[.. 4297], QrCodeEcc::Low).is_err());
+	}
+	
+	#[test]
+	fn test_data_codewords_and_segments() {
+		// Multiple blocks of two lengths, and mixed modes
+		let segs = vec![QrSegment::make_numeric("0123456789"), QrSegment::make_bytes(b"mixed content"),
+			QrSegment::make_eci(26), QrSegment::make_alphanumeric("ABC-123")];
+		let qr = QrCode::encode_segments_advanced(&segs, QrCodeEcc::Quartile,
+			Version::new(7), Version::new(7), None, false).unwrap();
+		let info = qr.block_structure();
+		assert_ne!(info.num_short_blocks, info.num_blocks);
+		
+		let data = qr.data_codewords();
+		assert_eq!(data.len(), QrCode::get_num_data_codewords(Version::new(7), QrCodeEcc::Quartile));
+		assert!(QrCode::encode_codewords(qr.version(), qr.error_correction_level(), &data, Some(qr.mask())) == qr);
+		assert!(qr.segments() == segs);
+		
+		let text = QrCode::encode_text("https://example.com/?q=1", QrCodeEcc::High).unwrap();
+		assert!(text.segments() == QrSegment::make_segments("https://example.com/?q=1"));
+		assert!(QrCode::encode_segments(&text.segments(), QrCodeEcc::High).unwrap() == text);
+	}
+	
+	// Expected sequences computed with an independent Reed-Solomon implementation,
+	// checked against the 1-M "HELLO WORLD" example of ISO/IEC 18004
+	#[test]
+	fn test_add_ecc_and_interleave_reference() {
+		let interleave = |ver: Version, ecl: QrCodeEcc, data: &[u8]| -> Vec<u8> {
+			QrCode::encode_codewords(ver, ecl, data, Some(Mask::new(0))).add_ecc_and_interleave(data)
+		};
+		
+		// Version 5-H: 2 short blocks of 11 and 2 long blocks of 12 data codewords, 22 ECC each
+		let ver = Version::new(5);
+		let info = QrCode::get_block_info(ver, QrCodeEcc::High);
+		assert_eq!((info.num_short_blocks, info.num_blocks, info.ecc_codewords_per_block), (2, 4, 22));
+		let data: Vec<u8> = (0 .. 46).map(|i| (i * 7 + 3) as u8).collect();
+		let expected: [u8; 134] = [
+			  3,  80, 157, 241,  10,  87, 164, 248,  17,  94, 171, 255,  24, 101, 178,   6,
+			 31, 108, 185,  13,  38, 115, 192,  20,  45, 122, 199,  27,  52, 129, 206,  34,
+			 59, 136, 213,  41,  66, 143, 220,  48,  73, 150, 227,  55, 234,  62, 218,  93,
+			252, 246,  26, 157, 138, 101, 111,  98,  20,  20,  26, 134, 202,  69, 232,  99,
+			 65, 192,  44, 226, 132, 212, 118, 218, 131,  29,  49,  45, 245,  75, 133,  76,
+			233, 115, 212, 227, 123, 102, 252, 222,  42, 184, 186,  69, 168,  10,  22, 158,
+			104,  74, 194, 133,  51, 249,  80, 148,  55,  73, 201, 230,  91,  63,  45,  97,
+			187, 117, 168,  63, 245, 107,  30, 202,   5, 112,  97,  96, 202, 179,  70, 177,
+			 20, 118, 192,   3, 249, 137,
+		];
+		assert_eq!(interleave(ver, QrCodeEcc::High, &data), expected);
+		
+		// Version 1-M: a single block
+		let data: Vec<u8> = (0 .. 16).map(|i| (i * 13 + 1) as u8).collect();
+		let expected: [u8; 26] = [
+			  1,  14,  27,  40,  53,  66,  79,  92, 105, 118, 131, 144, 157, 170, 183, 196,
+			224,  89,  94, 211,  80, 111,  46, 113,  58, 126,
+		];
+		assert_eq!(interleave(Version::new(1), QrCodeEcc::Medium, &data), expected);
+		
+		let hello: [u8; 16] = [32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17];
+		assert_eq!(interleave(Version::new(1), QrCodeEcc::Medium, &hello)[16 ..], [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]);
+	}
+	
+	#[test]
+	fn test_encode_text_best_effort() {
+		// 2000 bytes: over the version 40 capacity at High and Quartile, within it at Medium
+		let text = "a".repeat(2000);
+		let (qr, ecl) = QrCode::encode_text_best_effort(&text, QrCodeEcc::High, QrCodeEcc::Low).unwrap();
+		assert_eq!(ecl, QrCodeEcc::Medium);
+		assert_eq!(qr.error_correction_level(), QrCodeEcc::Medium);
+		assert!(QrCode::encode_text_best_effort(&text, QrCodeEcc::High, QrCodeEcc::Quartile).is_err());
+		
+		// Fitting at the preferred level, possibly boosted
+		let (_, ecl) = QrCode::encode_text_best_effort("short", QrCodeEcc::Low, QrCodeEcc::Low).unwrap();
+		assert_eq!(ecl, QrCodeEcc::High);
+		
+		let Err(err) = QrCode::encode_text_best_effort(&"a".repeat(3000), QrCodeEcc::Medium, QrCodeEcc::Low) else { panic!() };
+		assert!(err.is_hopeless());
+	}
+	
+	#[test]
+	fn test_invalid_version_range() {
+		let segs = QrSegment::make_segments("Hello");
+		let result = QrCode::encode_segments_advanced(&segs, QrCodeEcc::Low, Version::new(10), Version::new(5), None, true);
+		let Err(e) = result else { panic!("Expected an error") };
+		assert!(matches!(e, DataTooLong::InvalidVersionRange(min, max) if min.value() == 10 && max.value() == 5));
+		assert!(!e.is_hopeless());
+		assert_eq!(e.to_string(), "Min version 10 is greater than max version 5");
+		assert!(QrCode::encode_segments_advanced(&segs, QrCodeEcc::Low, Version::new(5), Version::new(5), None, true).is_ok());
+	}
+	
+}
@@ -0,0 +1,450 @@
+/* 
+ * QR Code generator library (Rust)
+ * 
+ * Copyright (c) Project Nayuki. (MIT License)
+ * Copyright (c) Abdulrhman Alkhodiry (aalkhodiry@gmail.com)
+ * 
+ * Raster rendering of fancy QR codes (requires the `raster` feature)
+ */
+
+//! Raster rendering of fancy QR codes, for PNG output without a browser.
+//! 
+//! This module mirrors the drawing operations of `FancyQr::render_svg` as
+//! `tiny-skia` path fills, so rounded modules, circles, finder shapes and
+//! gradients come out the same as in the SVG. It is only available with the
+//! `raster` feature enabled.
+//! 
+//! Text has no rasterizer here: a `center_text` badge is drawn without its
+//! label, and a `ring_text` caption only reserves its space on the canvas.
+
+use tiny_skia::{
+    Color, FillRule, FilterQuality, GradientStop, LinearGradient, Paint, PathBuilder,
+    Pattern, Pixmap, Point, Rect, Shader, SpreadMode, Stroke, Transform,
+};
+
+use crate::fancy::{palette_color, parse_hex_color, squircle_handle, FancyOptions, FancyQr, FinderLayer, FinderShape, Gradient, ModuleShape, OverlayShape, QuietZoneDecoration};
+
+/// The error type when a fancy QR code cannot be rasterized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RasterError {
+    /// The scale is zero or the image would be too large to allocate
+    InvalidSize,
+    /// A color option is not a "#RGB" or "#RRGGBB" hex color
+    InvalidColor(String),
+    /// The center image is not a base64 PNG or JPEG data URI, or failed to decode
+    UnsupportedImage(String),
+    /// Encoding the PNG failed
+    Encode(String),
+}
+
+impl std::error::Error for RasterError {}
+
+impl std::fmt::Display for RasterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::InvalidSize => write!(f, "Raster size is zero or too large"),
+            Self::InvalidColor(c) => write!(f, "Invalid hex color: {}", c),
+            Self::UnsupportedImage(msg) => write!(f, "Unsupported center image: {}", msg),
+            Self::Encode(msg) => write!(f, "PNG encoding failed: {}", msg),
+        }
+    }
+}
+
+/// Renders a fancy QR code to a pixmap, with each module `scale` pixels wide.
+/// 
+/// The canvas has the same layout as the SVG from `FancyQr::render_svg`, including
+/// the quiet zone and any room reserved for a ring caption.
+/// 
+/// A center image must be a `data:image/png;base64,...` or `data:image/jpeg;base64,...`
+/// URI; it is scaled to cover the safe zone like the SVG's `xMidYMid slice`.
+pub fn render_pixmap(qr: &FancyQr, options: &FancyOptions, scale: u32) -> Result<Pixmap, RasterError> {
+    let matrix_width = qr.qrcode().size() as usize;
+    let quiet_zone = qr.quiet_zone();
+    let full_width = matrix_width + (quiet_zone * 2);
+    let ring_pad = qr.ring_padding(options);
+    let canvas_width = full_width + ring_pad * 2;
+
+    let pixels = u32::try_from(canvas_width).ok()
+        .and_then(|w| w.checked_mul(scale))
+        .ok_or(RasterError::InvalidSize)?;
+    let mut pixmap = Pixmap::new(pixels, pixels).ok_or(RasterError::InvalidSize)?;
+
+    // Work in module units, with the code at the same coordinates as in the SVG,
+    // rotated about the canvas center like the SVG's `rotate(...)` group
+    let center = full_width as f32 / 2.0;
+    let ts = Transform::from_scale(scale as f32, scale as f32)
+        .pre_translate(ring_pad as f32, ring_pad as f32)
+        .pre_rotate_at(options.rotation.degrees() as f32, center, center);
+
+    // 1. Background Layer
+    let background = solid_paint(&options.color_background)?;
+    pixmap.fill(color(&options.color_background)?);
+
+    // Quiet zone decoration, confined to the outermost module ring
+    match options.quiet_zone_decoration.as_ref().filter(|_| quiet_zone > 0) {
+        Some(QuietZoneDecoration::InnerLine { color, width }) => {
+            let w = width.clamp(0.0, 1.0);
+            let mut pb = PathBuilder::new();
+            push_rounded_rect(&mut pb, w / 2.0, w / 2.0, full_width as f32 - w, 1.0);
+            if let Some(path) = pb.finish() {
+                let stroke = Stroke { width: w, ..Stroke::default() };
+                pixmap.stroke_path(&path, &solid_paint(color)?, &stroke, ts, None);
+            }
+        },
+        Some(QuietZoneDecoration::Dots { color }) => {
+            let mut pb = PathBuilder::new();
+            let last = full_width - 1;
+            for i in 0..last {
+                for (c, r) in [(i, 0), (last, i), (last - i, last), (0, last - i)] {
+                    pb.push_circle(c as f32 + 0.5, r as f32 + 0.5, 0.15);
+                }
+            }
+            fill(&mut pixmap, pb, &solid_paint(color)?, ts);
+        },
+        None => {},
+    }
+
+    let data_paint = match &options.gradient_data {
+        Some(g) => gradient_paint(g, quiet_zone, matrix_width)?,
+        None => solid_paint(&options.color_data)?,
+    };
+    let finder_paint = match &options.gradient_finder {
+        Some(g) => gradient_paint(g, quiet_zone, matrix_width)?,
+        None => solid_paint(&options.color_finder)?,
+    };
+
+    // 2. Render Data Modules (one path under a single fill, plus one for the safe zone frame
+    // and one per palette color)
+    let elements = qr.data_elements(options);
+    let mut pb = PathBuilder::new();
+    let mut frame_pb = PathBuilder::new();
+    let palette = options.palette.as_ref().map_or(&[][..], |(colors, _)| colors.as_slice());
+    let mut palette_pbs: Vec<PathBuilder> = palette.iter().map(|_| PathBuilder::new()).collect();
+    for r in 0..matrix_width {
+        for c in 0..matrix_width {
+            let Some((shape, len)) = elements[r * matrix_width + c] else {
+                continue;
+            };
+            let x = (c + quiet_zone) as f32;
+            let y = (r + quiet_zone) as f32;
+            let palette_index = palette_color(options, c, r, matrix_width)
+                .and_then(|color| palette.iter().position(|p| p == color));
+            let target = if options.ring_data_color.is_some() && FancyQr::is_safe_zone_frame(c, r, matrix_width, options) {
+                &mut frame_pb
+            } else if let Some(i) = palette_index {
+                &mut palette_pbs[i]
+            } else {
+                &mut pb
+            };
+            match shape {
+                ModuleShape::Square => push_rounded_rect(target, x, y, 1.0, 0.0),
+                ModuleShape::Circle => target.push_circle(x + 0.5, y + 0.5, 0.45),
+                ModuleShape::RoundedSquare(rad) => push_rounded_rect(target, x, y, 1.0, rad),
+                ModuleShape::VerticalBars { radius } => push_rounded_rect_xywh(target, x, y, 1.0, len as f32, radius),
+                ModuleShape::HorizontalBars { radius } => push_rounded_rect_xywh(target, x, y, len as f32, 1.0, radius),
+                ModuleShape::Squircle { n } => push_squircle(target, x + 0.5, y + 0.5, n),
+            }
+        }
+    }
+    fill(&mut pixmap, pb, &data_paint, ts);
+    for (color, palette_pb) in palette.iter().zip(palette_pbs) {
+        fill(&mut pixmap, palette_pb, &solid_paint(color)?, ts);
+    }
+    if let Some(ring_color) = &options.ring_data_color {
+        fill(&mut pixmap, frame_pb, &solid_paint(ring_color)?, ts);
+    }
+    if let Some((dot_color, radius)) = &options.background_dots {
+        let mut pb = PathBuilder::new();
+        for (c, r) in qr.background_dot_cells(options) {
+            pb.push_circle((c + quiet_zone) as f32 + 0.5, (r + quiet_zone) as f32 + 0.5, radius.clamp(0.0, 0.5));
+        }
+        fill(&mut pixmap, pb, &solid_paint(dot_color)?, ts);
+    }
+
+    // 3. Render Custom Finder Patterns
+    if options.custom_finders {
+        render_finder_patterns(&mut pixmap, qr, options, &finder_paint, &background, ts)?;
+    }
+
+    // 4. Render Center Overlay
+    let center_px = matrix_width as f32 / 2.0 + quiet_zone as f32;
+    let size_px = matrix_width as f32 * options.overlay_scale.value();
+    let start_px = center_px - (size_px / 2.0);
+    let has_overlay = options.center_image_url.is_some() || options.center_text.is_some();
+    if let Some((glass, opacity)) = options.overlay_glass.as_ref().filter(|_| has_overlay) {
+        let mut paint = solid_paint(glass)?;
+        let mut tint = color(glass)?;
+        tint.apply_opacity(opacity.clamp(0.0, 1.0));
+        paint.set_color(tint);
+        let mut pb = PathBuilder::new();
+        push_rounded_rect_xywh(&mut pb, start_px, start_px, size_px, size_px, 1.0);
+        fill(&mut pixmap, pb, &paint, ts);
+    }
+    if let Some(href) = &options.center_image_url {
+        let image = decode_data_uri(href)?;
+        let (w, h) = (image.width() as f32, image.height() as f32);
+        let k = (size_px / w).max(size_px / h);
+        let image_ts = Transform::from_row(k, 0.0, 0.0, k,
+            start_px + (size_px - w * k) / 2.0, start_px + (size_px - h * k) / 2.0);
+        let paint = Paint {
+            shader: Pattern::new(image.as_ref(), SpreadMode::Pad, FilterQuality::Bicubic, 1.0, image_ts),
+            anti_alias: true,
+            ..Paint::default()
+        };
+        match options.overlay_shape {
+            OverlayShape::Square => {
+                if let Some(rect) = Rect::from_xywh(start_px, start_px, size_px, size_px) {
+                    pixmap.fill_rect(rect, &paint, ts, None);
+                }
+            },
+            OverlayShape::Circle => {
+                let mut pb = PathBuilder::new();
+                pb.push_circle(center_px, center_px, size_px / 2.0);
+                fill(&mut pixmap, pb, &paint, ts);
+            },
+        }
+    } else if options.center_text.is_some() {
+        // The label badge, without its text
+        let mut pb = PathBuilder::new();
+        push_rounded_rect_xywh(&mut pb, start_px - 0.5, start_px + (size_px * 0.25), size_px + 1.0, size_px * 0.5, 1.0);
+        if let Some(path) = pb.finish() {
+            pixmap.fill_path(&path, &background, FillRule::Winding, ts, None);
+            let stroke = Stroke { width: 0.2, ..Stroke::default() };
+            pixmap.stroke_path(&path, &solid_paint(&options.color_data)?, &stroke, ts, None);
+        }
+    }
+
+    Ok(pixmap)
+}
+
+/// Renders a fancy QR code to PNG bytes, with each module `scale` pixels wide.
+/// 
+/// See `render_pixmap` for the layout and the supported center images.
+pub fn render_png(qr: &FancyQr, options: &FancyOptions, scale: u32) -> Result<Vec<u8>, RasterError> {
+    render_pixmap(qr, options, scale)?
+        .encode_png()
+        .map_err(|e| RasterError::Encode(e.to_string()))
+}
+
+// Mirrors `FancyQr::render_finder_patterns`, drawing the same boxes in the same order.
+fn render_finder_patterns(
+    pixmap: &mut Pixmap,
+    qr: &FancyQr,
+    options: &FancyOptions,
+    paint: &Paint,
+    background: &Paint,
+    ts: Transform,
+) -> Result<(), RasterError> {
+    let q = qr.quiet_zone() as f32;
+    for b in qr.finder_boxes(options).iter() {
+        let mut pb = PathBuilder::new();
+        push_finder_box(&mut pb, b.shape, b.x + q, b.y + q, b.size, b.rx);
+        match b.layer {
+            FinderLayer::Frame | FinderLayer::Ball => fill(pixmap, pb, paint, ts),
+            FinderLayer::Cutout => fill(pixmap, pb, background, ts),
+            FinderLayer::Ring(i) => {
+                let ring_color = options.finder_rings.as_ref().map_or("", |rings| rings[i].as_str());
+                fill(pixmap, pb, &solid_paint(ring_color)?, ts);
+            },
+        }
+    }
+    Ok(())
+}
+
+// Square-footprint finder element: a circle for `FinderShape::Dot`, else a (rounded) square.
+fn push_finder_box(pb: &mut PathBuilder, shape: FinderShape, x: f32, y: f32, size: f32, rx: f32) {
+    if shape == FinderShape::Dot {
+        pb.push_circle(x + size / 2.0, y + size / 2.0, size / 2.0);
+    } else {
+        push_rounded_rect(pb, x, y, size, rx);
+    }
+}
+
+fn push_rounded_rect(pb: &mut PathBuilder, x: f32, y: f32, size: f32, rx: f32) {
+    push_rounded_rect_xywh(pb, x, y, size, size, rx);
+}
+
+// Appends a rectangle with corner radius `rx`, clamped to half the shorter side as SVG does.
+fn push_rounded_rect_xywh(pb: &mut PathBuilder, x: f32, y: f32, w: f32, h: f32, rx: f32) {
+    let r = rx.min(w / 2.0).min(h / 2.0);
+    if r <= 0.0 {
+        if let Some(rect) = Rect::from_xywh(x, y, w, h) {
+            pb.push_rect(rect);
+        }
+        return;
+    }
+    // Cubic approximation of a quarter circle
+    let k = r * 0.552_284_8;
+    let (x2, y2) = (x + w, y + h);
+    pb.move_to(x + r, y);
+    pb.line_to(x2 - r, y);
+    pb.cubic_to(x2 - r + k, y, x2, y + r - k, x2, y + r);
+    pb.line_to(x2, y2 - r);
+    pb.cubic_to(x2, y2 - r + k, x2 - r + k, y2, x2 - r, y2);
+    pb.line_to(x + r, y2);
+    pb.cubic_to(x + r - k, y2, x, y2 - r + k, x, y2 - r);
+    pb.line_to(x, y + r);
+    pb.cubic_to(x, y + r - k, x + r - k, y, x + r, y);
+    pb.close();
+}
+
+// Appends a module-sized superellipse centered at (cx, cy), as `render_svg` draws it.
+fn push_squircle(pb: &mut PathBuilder, cx: f32, cy: f32, n: f32) {
+    let d = squircle_handle(n) * 0.5;
+    let (l, t, r, b) = (cx - 0.5, cy - 0.5, cx + 0.5, cy + 0.5);
+    pb.move_to(r, cy);
+    pb.cubic_to(r, cy + d, cx + d, b, cx, b);
+    pb.cubic_to(cx - d, b, l, cy + d, l, cy);
+    pb.cubic_to(l, cy - d, cx - d, t, cx, t);
+    pb.cubic_to(cx + d, t, r, cy - d, r, cy);
+    pb.close();
+}
+
+fn fill(pixmap: &mut Pixmap, pb: PathBuilder, paint: &Paint, ts: Transform) {
+    if let Some(path) = pb.finish() {
+        pixmap.fill_path(&path, paint, FillRule::Winding, ts, None);
+    }
+}
+
+fn color(hex: &str) -> Result<Color, RasterError> {
+    let (r, g, b) = parse_hex_color(hex).ok_or_else(|| RasterError::InvalidColor(hex.to_string()))?;
+    Ok(Color::from_rgba8(r, g, b, 255))
+}
+
+fn solid_paint(hex: &str) -> Result<Paint<'static>, RasterError> {
+    let mut paint = Paint::default();
+    paint.set_color(color(hex)?);
+    paint.anti_alias = true;
+    Ok(paint)
+}
+
+// Same geometry as the SVG's userSpaceOnUse gradient over the matrix area.
+fn gradient_paint(gradient: &Gradient, quiet_zone: usize, matrix_width: usize) -> Result<Paint<'static>, RasterError> {
+    let (x1, y1, x2, y2) = gradient.direction.endpoints(quiet_zone as f32, matrix_width as f32);
+    let (start, end) = (color(&gradient.start)?, color(&gradient.end)?);
+    let shader = LinearGradient::new(
+        Point::from_xy(x1, y1),
+        Point::from_xy(x2, y2),
+        vec![GradientStop::new(0.0, start), GradientStop::new(1.0, end)],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap_or(Shader::SolidColor(start));
+    Ok(Paint { shader, anti_alias: true, ..Paint::default() })
+}
+
+// Decodes a base64 PNG or JPEG data URI into a premultiplied pixmap.
+fn decode_data_uri(href: &str) -> Result<Pixmap, RasterError> {
+    let unsupported = |msg: &str| RasterError::UnsupportedImage(msg.to_string());
+    let rest = href.trim().strip_prefix("data:").ok_or_else(|| unsupported("not a data URI"))?;
+    let (mime, data) = rest.split_once(";base64,").ok_or_else(|| unsupported("not base64 encoded"))?;
+    if !matches!(mime, "image/png" | "image/jpeg" | "image/jpg") {
+        return Err(unsupported(mime));
+    }
+    let bytes = crate::render::base64_decode(data).ok_or_else(|| unsupported("invalid base64"))?;
+    let rgba = image::load_from_memory(&bytes)
+        .map_err(|e| RasterError::UnsupportedImage(e.to_string()))?
+        .to_rgba8();
+    let (w, h) = rgba.dimensions();
+    let mut pixmap = Pixmap::new(w, h).ok_or(RasterError::InvalidSize)?;
+    for (dst, src) in pixmap.pixels_mut().iter_mut().zip(rgba.pixels()) {
+        let [r, g, b, a] = src.0;
+        *dst = tiny_skia::ColorU8::from_rgba(r, g, b, a).premultiply();
+    }
+    Ok(pixmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QrCodeEcc;
+
+    // Golden image of "https://example.com" at High ECC with circle modules, 8 px per module
+    const GOLDEN_CIRCLE_X8: &[u8] = include_bytes!("../tests/golden/circle_x8.png");
+
+    fn circle_options() -> FancyOptions {
+        FancyOptions {
+            shape_module: ModuleShape::Circle,
+            shape_finder: FinderShape::Rounded(1.5),
+            ..FancyOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_golden_circle_modules() {
+        let qr = FancyQr::from_text_with_ecc("https://example.com", QrCodeEcc::High).unwrap();
+        let pixmap = render_pixmap(&qr, &circle_options(), 8).unwrap();
+        let golden = image::load_from_memory(GOLDEN_CIRCLE_X8).unwrap().to_rgba8();
+        assert_eq!((pixmap.width(), pixmap.height()), golden.dimensions());
+
+        // Allow small anti-aliasing differences between rasterizer versions
+        let differing = pixmap.pixels().iter().zip(golden.pixels())
+            .filter(|(p, g)| {
+                let p = p.demultiply();
+                let actual = [p.red(), p.green(), p.blue(), p.alpha()];
+                actual.iter().zip(g.0.iter()).any(|(a, b)| a.abs_diff(*b) > 16)
+            })
+            .count();
+        assert!(differing * 200 < golden.pixels().len(), "{} pixels differ", differing);
+    }
+
+    #[test]
+    fn test_module_centers_match_code() {
+        let qr = FancyQr::from_text("Raster").unwrap();
+        let scale = 6;
+        let mut options = circle_options();
+        options.shape_finder = FinderShape::Square;
+        let pixmap = render_pixmap(&qr, &options, scale).unwrap();
+        let size = qr.qrcode().size();
+        for y in 0..size {
+            for x in 0..size {
+                let px = (x as u32 + qr.quiet_zone() as u32) * scale + scale / 2;
+                let py = (y as u32 + qr.quiet_zone() as u32) * scale + scale / 2;
+                let dark = pixmap.pixel(px, py).unwrap().red() < 128;
+                assert_eq!(dark, qr.qrcode().get_module(x, y), "module ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotation_matches_svg() {
+        let qr = FancyQr::from_text("Raster").unwrap();
+        let scale = 6;
+        let mut options = circle_options();
+        options.shape_finder = FinderShape::Square;
+        options.rotation = crate::render::Rotation::Deg90;
+        let full_width = qr.qrcode().size() as f32 + qr.quiet_zone() as f32 * 2.0;
+        let c = full_width / 2.0;
+        assert!(qr.render_svg(&options).contains(&format!(r#"<g transform="rotate(90 {c} {c})">"#)));
+        
+        // rotate(90 c c) maps (x, y) to (2c - y, x)
+        let pixmap = render_pixmap(&qr, &options, scale).unwrap();
+        let size = qr.qrcode().size();
+        for y in 0..size {
+            for x in 0..size {
+                let mx = (x as usize + qr.quiet_zone()) as f32 + 0.5;
+                let my = (y as usize + qr.quiet_zone()) as f32 + 0.5;
+                let (px, py) = ((2.0 * c - my) * scale as f32, mx * scale as f32);
+                let dark = pixmap.pixel(px as u32, py as u32).unwrap().red() < 128;
+                assert_eq!(dark, qr.qrcode().get_module(x, y), "module ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_raster_errors() {
+        let qr = FancyQr::from_text("Raster").unwrap();
+        assert_eq!(render_pixmap(&qr, &FancyOptions::default(), 0).unwrap_err(), RasterError::InvalidSize);
+
+        let options = FancyOptions {
+            color_data: "black".to_string(),
+            ..FancyOptions::default()
+        };
+        assert_eq!(render_pixmap(&qr, &options, 4).unwrap_err(), RasterError::InvalidColor("black".to_string()));
+
+        let options = FancyOptions {
+            center_image_url: Some("https://example.com/logo.png".to_string()),
+            ..FancyOptions::default()
+        };
+        assert!(matches!(render_pixmap(&qr, &options, 4), Err(RasterError::UnsupportedImage(_))));
+    }
+}
@@ -13,6 +13,7 @@
 //! including SVG and text output.
 
 use crate::qrcode::QrCode;
+use crate::fancy::GradientDirection;
 
 /// Renders a QR code as a simple SVG string.
 /// 
@@ -22,6 +23,13 @@ use crate::qrcode::QrCode;
 /// * `border` - The size of the quiet zone (white border) in modules
 /// * `module_size` - The size of each module in pixels (default: 1)
 /// 
+/// All coordinates are integer multiples of `module_size`, so edges stay on the pixel grid.
+/// 
+/// The output is deterministic: the same code and arguments give a byte-identical document
+/// on every run and platform, so it can be cached or checked in. Numbers never use locale
+/// dependent separators, and every SVG renderer in this crate that draws at fractional
+/// positions, `FancyQr::render_svg` included, prints them with at most 3 decimals.
+/// 
 /// # Example
 /// 
 /// ```rust
@@ -32,22 +40,452 @@ use crate::qrcode::QrCode;
 /// let svg = to_svg_string(&qr, 4, 10);
 /// ```
 pub fn to_svg_string(qr: &QrCode, border: i32, module_size: i32) -> String {
+    to_svg_string_with(qr, &SvgOptions { border, module_size, ..SvgOptions::default() })
+}
+
+/// Renders a QR code as a simple SVG document in UTF-8 bytes, ready to write to a
+/// response body or file.
+/// 
+/// The output is exactly `to_svg_string(qr, border, module_size)`. The string's buffer is
+/// handed over as is, so no bytes are copied or re-encoded.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::{QrCode, QrCodeEcc};
+/// use qrcode_lib::render::to_svg_bytes;
+/// 
+/// let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+/// let body: Vec<u8> = to_svg_bytes(&qr, 4, 10);
+/// assert!(body.starts_with(b"<svg"));
+/// ```
+pub fn to_svg_bytes(qr: &QrCode, border: i32, module_size: i32) -> Vec<u8> {
+    to_svg_string(qr, border, module_size).into_bytes()
+}
+
+/// A clockwise rotation of the rendered code, in 90° steps.
+/// 
+/// The module data is unchanged; the drawing is wrapped in a group rotated about the
+/// center of the square viewBox, so it stays within the same bounds.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Rotation {
+    /// Upright
+    #[default]
+    None,
+    /// A quarter turn clockwise
+    Deg90,
+    /// A half turn
+    Deg180,
+    /// Three quarter turns clockwise
+    Deg270,
+}
+
+impl Rotation {
+    /// Returns the clockwise angle in degrees: 0, 90, 180 or 270.
+    pub fn degrees(self) -> u32 {
+        match self {
+            Rotation::None   => 0,
+            Rotation::Deg90  => 90,
+            Rotation::Deg180 => 180,
+            Rotation::Deg270 => 270,
+        }
+    }
+}
+
+// Returns the opening `<g>` that rotates the drawing about the center of a square viewBox
+// starting at `origin` and `width` wide, or `None` when upright.
+pub(crate) fn rotation_group(rotation: Rotation, origin: f32, width: f32) -> Option<String> {
+    if rotation == Rotation::None {
+        return None;
+    }
+    let c = fmt_coord(origin + width / 2.0);
+    Some(format!(r#"<g transform="rotate({} {c} {c})">"#, rotation.degrees()))
+}
+
+// Formats a fractional SVG coordinate or length with at most 3 decimals, dropping trailing
+// zeros (so whole numbers print as integers). Unlike the shortest round-trip `{}` form, the
+// text does not depend on float noise such as 14.500001, and `-0` prints as `0`.
+pub(crate) fn fmt_coord(v: f32) -> String {
+    let s = format!("{:.3}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    match s {
+        "-0" => "0".to_string(),
+        _ => s.to_string(),
+    }
+}
+
+/// Options for `to_svg_string_with`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgOptions {
+    /// The size of the quiet zone (white border) in modules
+    pub border: i32,
+    /// The size of each module in pixels
+    pub module_size: i32,
+    /// Emit one `<rect>` per dark module instead of a single `<path>`.
+    /// Some SVG-to-PDF converters handle many rects better than one huge path.
+    pub rect_per_module: bool,
+    /// With `rect_per_module`, merge horizontal runs of dark modules into one `<rect>` each
+    pub merge_runs: bool,
+    /// `id` attribute of the root `<svg>` element
+    pub id: Option<String>,
+    /// `class` attribute of the root `<svg>` element
+    pub class: Option<String>,
+    /// Prepend the XML declaration and SVG 1.1 `DOCTYPE`, making the output a complete
+    /// standalone `.svg` file for strict XML parsers. Leave unset for inline embedding in HTML.
+    pub standalone: bool,
+    /// Rotate the drawing within the viewBox
+    pub rotation: Rotation,
+    /// Corner radius of the background rect in pixels (SVG user units), so the code sits
+    /// on a rounded panel. 0.0 keeps square corners. Keep it within the quiet zone.
+    pub background_radius: f32,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            border: 4,
+            module_size: 1,
+            rect_per_module: false,
+            merge_runs: false,
+            id: None,
+            class: None,
+            standalone: false,
+            rotation: Rotation::None,
+            background_radius: 0.0,
+        }
+    }
+}
+
+/// Renders a QR code as a simple SVG string with the given options.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::{QrCode, QrCodeEcc};
+/// use qrcode_lib::render::{to_svg_string_with, SvgOptions};
+/// 
+/// let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+/// let options = SvgOptions { rect_per_module: true, ..SvgOptions::default() };
+/// let svg = to_svg_string_with(&qr, &options);
+/// ```
+pub fn to_svg_string_with(qr: &QrCode, options: &SvgOptions) -> String {
+    let size = qr.size();
+    let border = options.border;
+    let module_size = options.module_size;
+    let full_size = qr.size_for_render(border, module_size);
+    
+    let mut svg = String::new();
+    if options.standalone {
+        svg.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        svg.push_str("<!DOCTYPE svg PUBLIC \"-//W3C//DTD SVG 1.1//EN\" \"http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd\">\n");
+    }
+    svg.push_str(&format!(
+        r##"<svg{a} xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="0 0 {w} {w}" stroke="none">"##,
+        a = root_attributes(options.id.as_deref(), options.class.as_deref()),
+        w = full_size
+    ));
+    svg.push('\n');
+    let rotation = rotation_group(options.rotation, 0.0, full_size as f32);
+    if let Some(group) = &rotation {
+        svg.push_str(group);
+    }
+    
+    // Background
+    let rx = match options.background_radius {
+        r if r > 0.0 => format!(r#" rx="{}""#, fmt_coord(r)),
+        _ => String::new(),
+    };
+    svg.push_str(&format!(
+        r##"<rect width="{w}" height="{w}"{rx} fill="#FFFFFF"/>"##,
+        w = full_size
+    ));
+    svg.push('\n');
+    
+    // Modules
+    if options.rect_per_module {
+        svg.push_str(r##"<g fill="#000000">"##);
+        for y in 0..size {
+            let mut x = 0;
+            while x < size {
+                if !qr.get_module(x, y) {
+                    x += 1;
+                    continue;
+                }
+                let len = if options.merge_runs {
+                    (x..size).take_while(|&xx| qr.get_module(xx, y)).count() as i32
+                } else {
+                    1
+                };
+                svg.push_str(&format!(
+                    r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#,
+                    (x + border) * module_size, (y + border) * module_size, len * module_size, module_size
+                ));
+                x += len;
+            }
+        }
+        svg.push_str("</g>");
+    } else {
+        svg.push_str(r##"<path d=""##);
+        for y in 0..size {
+            for x in 0..size {
+                if qr.get_module(x, y) {
+                    let px = (x + border) * module_size;
+                    let py = (y + border) * module_size;
+                    svg.push_str(&format!("M{},{}h{}v{}h-{}z", px, py, module_size, module_size, module_size));
+                }
+            }
+        }
+        svg.push_str(r##"" fill="#000000"/>"##);
+    }
+    if rotation.is_some() {
+        svg.push_str("</g>");
+    }
+    svg.push_str("\n</svg>");
+    
+    svg
+}
+
+/// Renders a QR code as a simple SVG (see `to_svg_string`) wrapped in a base64
+/// `data:image/svg+xml` URI, ready for an `<img src>` or CSS `url()`.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::{QrCode, QrCodeEcc};
+/// use qrcode_lib::render::to_svg_data_uri;
+/// 
+/// let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+/// let src = to_svg_data_uri(&qr, 4, 10);
+/// assert!(src.starts_with("data:image/svg+xml;base64,PHN2Zy"));
+/// ```
+pub fn to_svg_data_uri(qr: &QrCode, border: i32, module_size: i32) -> String {
+    svg_data_uri(&to_svg_string(qr, border, module_size))
+}
+
+/// Wraps any SVG document in a base64 `data:image/svg+xml` URI, e.g. to embed a
+/// logo as a `FancyOptions::center_image_url`.
+pub fn svg_data_uri(svg: &str) -> String {
+    format!("data:image/svg+xml;base64,{}", base64_encode(svg.as_bytes()))
+}
+
+// Encodes bytes as standard base64 with padding.
+pub(crate) fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |acc, (i, &b)| acc | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(char::from(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+// Decodes standard base64, ignoring whitespace and padding.
+#[cfg(any(test, feature = "raster"))]
+pub(crate) fn base64_decode(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    for b in input.bytes().filter(|b| !b.is_ascii_whitespace() && *b != b'=') {
+        let v = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        acc = (acc << 6) | u32::from(v);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+// Formats the optional `id` and `class` attributes of a root `<svg>` element, each with a leading space.
+pub(crate) fn root_attributes(id: Option<&str>, class: Option<&str>) -> String {
+    let mut attrs = String::new();
+    if let Some(id) = id {
+        attrs.push_str(&format!(r#" id="{}""#, escape_xml(id)));
+    }
+    if let Some(class) = class {
+        attrs.push_str(&format!(r#" class="{}""#, escape_xml(class)));
+    }
+    attrs
+}
+
+// Escapes the characters that are special in XML text and attribute values.
+pub(crate) fn escape_xml(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// The module shape for `to_svg_string_shaped`.
+/// 
+/// A lightweight counterpart of `fancy::ModuleShape` for the plain renderer.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SimpleShape {
+    /// Square modules, drawn as one merged path like `to_svg_string`
+    Square,
+    /// Circular modules (diameter 90% of the module size)
+    Circle,
+    /// Squares with rounded corners. Radius is 0.0 to 0.5 (relative to module size).
+    RoundedSquare(f32),
+}
+
+/// Renders a QR code as a simple SVG string with the given module shape.
+/// 
+/// Produces the same layout as `to_svg_string`; only the modules are drawn differently.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::{QrCode, QrCodeEcc};
+/// use qrcode_lib::render::{to_svg_string_shaped, SimpleShape};
+/// 
+/// let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+/// let svg = to_svg_string_shaped(&qr, 4, 10, SimpleShape::Circle);
+/// ```
+pub fn to_svg_string_shaped(qr: &QrCode, border: i32, module_size: i32, shape: SimpleShape) -> String {
+    if shape == SimpleShape::Square {
+        return to_svg_string(qr, border, module_size);
+    }
+    
     let size = qr.size();
-    let full_size = (size + border * 2) * module_size;
+    let full_size = qr.size_for_render(border, module_size);
+    let m = module_size as f32;
     
     let mut svg = String::new();
     svg.push_str(&format!(
         r##"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="0 0 {w} {w}" stroke="none">"##,
         w = full_size
     ));
-    svg.push_str("\n");
+    svg.push('\n');
     
     // Background
     svg.push_str(&format!(
         r##"<rect width="{w}" height="{w}" fill="#FFFFFF"/>"##,
         w = full_size
     ));
-    svg.push_str("\n");
+    svg.push('\n');
+    
+    // Modules
+    svg.push_str(r##"<g fill="#000000">"##);
+    for y in 0..size {
+        for x in 0..size {
+            if qr.get_module(x, y) {
+                let px = (x + border) * module_size;
+                let py = (y + border) * module_size;
+                match shape {
+                    SimpleShape::Circle => svg.push_str(&format!(
+                        r#"<circle cx="{}" cy="{}" r="{}"/>"#,
+                        fmt_coord(px as f32 + m / 2.0), fmt_coord(py as f32 + m / 2.0), fmt_coord(m * 0.45)
+                    )),
+                    SimpleShape::RoundedSquare(r) => svg.push_str(&format!(
+                        r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}"/>"#,
+                        px, py, module_size, module_size, fmt_coord(r * m)
+                    )),
+                    SimpleShape::Square => unreachable!(),
+                }
+            }
+        }
+    }
+    svg.push_str("</g>");
+    svg.push_str("\n</svg>");
+    
+    svg
+}
+
+/// Renders a QR code as a simple SVG string with the modules filled by a linear gradient.
+/// 
+/// Produces the same layout as `to_svg_string`, with the merged module path filled
+/// by a gradient from color `from` to `to` (hex format, e.g. "#4d3695") across the
+/// module matrix. This is a lighter entry point than `fancy::FancyQr` for quick branded codes.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::{QrCode, QrCodeEcc};
+/// use qrcode_lib::fancy::GradientDirection;
+/// use qrcode_lib::render::to_svg_string_gradient;
+/// 
+/// let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+/// let svg = to_svg_string_gradient(&qr, 4, 10, "#4d3695", "#3B82F6", GradientDirection::DiagonalTLBR);
+/// ```
+pub fn to_svg_string_gradient(qr: &QrCode, border: i32, module_size: i32, from: &str, to: &str, direction: GradientDirection) -> String {
+    let options = SvgOptions { border, module_size, ..SvgOptions::default() };
+    to_svg_string_gradient_with(qr, &options, from, to, direction)
+}
+
+/// Renders a QR code like `to_svg_string_gradient`, with the border, module size and
+/// root `id`/`class` taken from the options.
+/// 
+/// With an `id`, the gradient's id becomes `{id}-gradient`, so several such SVGs can be
+/// inlined on one page without their gradients colliding. Without one it is `qr-gradient`.
+/// The other options are ignored.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::{QrCode, QrCodeEcc};
+/// use qrcode_lib::fancy::GradientDirection;
+/// use qrcode_lib::render::{to_svg_string_gradient_with, SvgOptions};
+/// 
+/// let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+/// let options = SvgOptions { id: Some("promo".to_string()), ..SvgOptions::default() };
+/// let svg = to_svg_string_gradient_with(&qr, &options, "#4d3695", "#3B82F6", GradientDirection::Vertical);
+/// assert!(svg.contains(r#"fill="url(#promo-gradient)""#));
+/// ```
+pub fn to_svg_string_gradient_with(qr: &QrCode, options: &SvgOptions, from: &str, to: &str, direction: GradientDirection) -> String {
+    let size = qr.size();
+    let border = options.border;
+    let module_size = options.module_size;
+    let full_size = qr.size_for_render(border, module_size);
+    let gradient_id = match &options.id {
+        Some(id) => escape_xml(&format!("{}-gradient", id)),
+        None => "qr-gradient".to_string(),
+    };
+    let (x1, y1, x2, y2) = direction.endpoints((border * module_size) as f32, (size * module_size) as f32);
+    let (x1, y1, x2, y2) = (fmt_coord(x1), fmt_coord(y1), fmt_coord(x2), fmt_coord(y2));
+    
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        r##"<svg{a} xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="0 0 {w} {w}" stroke="none">"##,
+        a = root_attributes(options.id.as_deref(), options.class.as_deref()),
+        w = full_size
+    ));
+    svg.push('\n');
+    
+    // Gradient
+    svg.push_str(&format!(
+        r#"<defs><linearGradient id="{gradient_id}" gradientUnits="userSpaceOnUse" x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}"><stop offset="0" stop-color="{from}"/><stop offset="1" stop-color="{to}"/></linearGradient></defs>"#
+    ));
+    svg.push('\n');
+    
+    // Background
+    svg.push_str(&format!(
+        r##"<rect width="{w}" height="{w}" fill="#FFFFFF"/>"##,
+        w = full_size
+    ));
+    svg.push('\n');
     
     // Modules
     svg.push_str(r##"<path d=""##);
@@ -60,12 +498,181 @@ pub fn to_svg_string(qr: &QrCode, border: i32, module_size: i32) -> String {
             }
         }
     }
+    svg.push_str(&format!(r#"" fill="url(#{})"/>"#, gradient_id));
+    svg.push_str("\n</svg>");
+    
+    svg
+}
+
+/// Renders only the modules in a rectangular region of a QR code, e.g. to load a large
+/// code in tiles or to inspect part of it zoomed in.
+/// 
+/// The region spans columns `x0 .. x1` and rows `y0 .. y1` (upper bounds exclusive), in
+/// module coordinates without a border. It may extend past the code, where modules are
+/// light. Modules keep their position in the full code, so the viewBox starts at
+/// `(x0, y0) * module_size` and tiles of adjacent regions line up.
+/// 
+/// Panics if the region is empty.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::{QrCode, QrCodeEcc};
+/// use qrcode_lib::render::to_svg_region;
+/// 
+/// let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+/// let top_left = to_svg_region(&qr, 0, 0, 7, 7, 10);
+/// assert!(top_left.contains(r#"viewBox="0 0 70 70""#));
+/// ```
+pub fn to_svg_region(qr: &QrCode, x0: i32, y0: i32, x1: i32, y1: i32, module_size: i32) -> String {
+    assert!(x0 < x1 && y0 < y1, "Empty region");
+    let (w, h) = ((x1 - x0) * module_size, (y1 - y0) * module_size);
+    let (ox, oy) = (x0 * module_size, y0 * module_size);
+    
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="{ox} {oy} {w} {h}" stroke="none">"##
+    ));
+    svg.push('\n');
+    
+    // Background
+    svg.push_str(&format!(
+        r##"<rect x="{ox}" y="{oy}" width="{w}" height="{h}" fill="#FFFFFF"/>"##
+    ));
+    svg.push('\n');
+    
+    // Modules
+    svg.push_str(r##"<path d=""##);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            if qr.get_module(x, y) {
+                svg.push_str(&format!("M{},{}h{}v{}h-{}z", x * module_size, y * module_size, module_size, module_size, module_size));
+            }
+        }
+    }
     svg.push_str(r##"" fill="#000000"/>"##);
     svg.push_str("\n</svg>");
     
     svg
 }
 
+/// Renders a QR code as an SVG string with a physical size, for print.
+/// 
+/// The root `<svg>` gets `width` and `height` of `width_mm` millimeters (including the
+/// border), and the viewBox is measured in device dots at the given DPI, so the code
+/// fills exactly the requested size when printed at that resolution.
+/// 
+/// Panics if `width_mm` or `dpi` is not positive and finite.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::{QrCode, QrCodeEcc};
+/// use qrcode_lib::render::to_svg_physical;
+/// 
+/// let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+/// let svg = to_svg_physical(&qr, 4, 30.0, 300.0); // 30 mm wide at 300 DPI
+/// assert!(svg.contains(r#"width="30mm""#));
+/// ```
+pub fn to_svg_physical(qr: &QrCode, border: i32, width_mm: f32, dpi: f32) -> String {
+    assert!(width_mm.is_finite() && width_mm > 0.0, "Width must be positive");
+    assert!(dpi.is_finite() && dpi > 0.0, "DPI must be positive");
+    let size = qr.size();
+    let dots = width_mm / 25.4 * dpi;
+    let module_size = dots / (size + border * 2) as f32;
+    let m = fmt_coord(module_size);
+    
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{mm}mm" height="{mm}mm" viewBox="0 0 {w} {w}" stroke="none">"##,
+        mm = fmt_coord(width_mm), w = fmt_coord(dots)
+    ));
+    svg.push('\n');
+    
+    // Background
+    svg.push_str(&format!(
+        r##"<rect width="{w}" height="{w}" fill="#FFFFFF"/>"##,
+        w = fmt_coord(dots)
+    ));
+    svg.push('\n');
+    
+    // Modules
+    svg.push_str(r##"<path d=""##);
+    for y in 0..size {
+        for x in 0..size {
+            if qr.get_module(x, y) {
+                let px = fmt_coord((x + border) as f32 * module_size);
+                let py = fmt_coord((y + border) as f32 * module_size);
+                svg.push_str(&format!("M{},{}h{}v{}h-{}z", px, py, m, m, m));
+            }
+        }
+    }
+    svg.push_str(r##"" fill="#000000"/>"##);
+    svg.push_str("\n</svg>");
+    
+    svg
+}
+
+/// Serializes a QR code's modules and metadata as JSON, for external renderers
+/// such as a canvas front-end.
+/// 
+/// The output has the form `{"size":n,"border":b,"modules":[[true,false,...],...],"version":v,"ecc":"High","mask":m}`,
+/// where `modules` holds the `size` rows of `to_matrix` (without the border) and `border`
+/// is the quiet zone the consumer should leave around them.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::{QrCode, QrCodeEcc};
+/// use qrcode_lib::render::to_json;
+/// 
+/// let qr = QrCode::encode_text("Hello", QrCodeEcc::High).unwrap();
+/// let json = to_json(&qr, 4);
+/// assert!(json.starts_with(r#"{"size":21,"border":4,"modules":[["#));
+/// ```
+pub fn to_json(qr: &QrCode, border: i32) -> String {
+    let rows: Vec<String> = qr.to_matrix()
+        .iter()
+        .map(|row| format!("[{}]", row.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(",")))
+        .collect();
+    format!(
+        r#"{{"size":{},"border":{},"modules":[{}],"version":{},"ecc":"{}","mask":{}}}"#,
+        qr.size(), border, rows.join(","), qr.version().value(), qr.error_correction_level(), qr.mask().value()
+    )
+}
+
+/// Renders a QR code as a packed 1-bit-per-pixel buffer, one pixel per module, for
+/// blitting to monochrome displays.
+/// 
+/// Returns the buffer along with its width and height in pixels, both `size + 2 * border`.
+/// Rows are stored top to bottom, each packed most significant bit first and padded
+/// with zero bits to a whole byte. A set bit is a dark module; the border is light.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use qrcode_lib::{QrCode, QrCodeEcc};
+/// use qrcode_lib::render::to_packed_bits;
+/// 
+/// let qr = QrCode::encode_text("Hello", QrCodeEcc::Low).unwrap();
+/// let (bits, width, height) = to_packed_bits(&qr, 1);
+/// assert_eq!((width, height), (23, 23));
+/// assert_eq!(bits.len(), 3 * 23);
+/// ```
+pub fn to_packed_bits(qr: &QrCode, border: i32) -> (Vec<u8>, usize, usize) {
+    let width = qr.size_for_render(border, 1) as usize;
+    let stride = width.div_ceil(8);
+    let mut bits = vec![0u8; stride * width];
+    for y in 0..width {
+        for x in 0..width {
+            if qr.get_module(x as i32 - border, y as i32 - border) {
+                bits[y * stride + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+    (bits, width, width)
+}
+
 /// Renders a QR code as ASCII art for terminal display.
 /// 
 /// Uses Unicode block characters for a compact representation.
@@ -161,6 +768,48 @@ mod tests {
         assert!(svg.ends_with("</svg>"));
     }
     
+    #[test]
+    fn test_svg_rect_per_module() {
+        let qr = QrCode::encode_text("Test", QrCodeEcc::Low).unwrap();
+        let dark = (0..qr.size()).flat_map(|y| (0..qr.size()).map(move |x| (x, y)))
+            .filter(|&(x, y)| qr.get_module(x, y))
+            .count();
+        let mut options = SvgOptions { border: 4, module_size: 10, rect_per_module: true, ..SvgOptions::default() };
+        let svg = to_svg_string_with(&qr, &options);
+        assert_eq!(svg.matches("<rect x=").count(), dark);
+        assert!(!svg.contains("<path"));
+        
+        options.merge_runs = true;
+        let merged = to_svg_string_with(&qr, &options);
+        let count = merged.matches("<rect x=").count();
+        assert!(0 < count && count < dark);
+        let covered: usize = merged.match_indices(r#" width=""#)
+            .skip(1) // background
+            .map(|(i, m)| merged[i + m.len()..].split('"').next().unwrap().parse::<usize>().unwrap() / 10)
+            .sum();
+        assert_eq!(covered, dark);
+        
+        options.rect_per_module = false;
+        assert_eq!(to_svg_string_with(&qr, &options), to_svg_string(&qr, 4, 10));
+    }
+    
+    #[test]
+    fn test_svg_shaped_circles() {
+        let qr = QrCode::encode_text("Test", QrCodeEcc::Low).unwrap();
+        let svg = to_svg_string_shaped(&qr, 4, 10, SimpleShape::Circle);
+        let dark = (0..qr.size()).flat_map(|y| (0..qr.size()).map(move |x| (x, y)))
+            .filter(|&(x, y)| qr.get_module(x, y))
+            .collect::<Vec<_>>();
+        assert_eq!(svg.matches("<circle ").count(), dark.len());
+        for (x, y) in dark {
+            let center = format!(r#"<circle cx="{}" cy="{}" r="4.5"/>"#, (x + 4) * 10 + 5, (y + 4) * 10 + 5);
+            assert!(svg.contains(&center), "missing {}", center);
+        }
+        assert!(!svg.contains("<path"));
+        
+        assert_eq!(to_svg_string_shaped(&qr, 4, 10, SimpleShape::Square), to_svg_string(&qr, 4, 10));
+    }
+    
     #[test]
     fn test_ascii_art() {
         let qr = QrCode::encode_text("Hi", QrCodeEcc::Low).unwrap();
@@ -176,5 +825,226 @@ mod tests {
         assert!(debug.contains('0'));
         assert!(debug.contains('1'));
     }
+    
+    #[test]
+    fn test_svg_gradient() {
+        let qr = QrCode::encode_text("Test", QrCodeEcc::Low).unwrap();
+        let svg = to_svg_string_gradient(&qr, 4, 10, "#000000", "#4d3695", GradientDirection::DiagonalTRBL);
+        // Matrix spans 40..250 in user units
+        assert!(svg.contains(r#"x1="250" y1="40" x2="40" y2="250""#));
+        assert!(svg.contains(r##"<stop offset="0" stop-color="#000000"/><stop offset="1" stop-color="#4d3695"/>"##));
+        assert_eq!(svg.matches("<linearGradient").count(), 1);
+        assert!(svg.contains(r#"fill="url(#qr-gradient)""#));
+        
+        let svg = to_svg_string_gradient(&qr, 4, 10, "#000000", "#4d3695", GradientDirection::DiagonalTLBR);
+        assert!(svg.contains(r#"x1="40" y1="40" x2="250" y2="250""#));
+    }
+    
+    #[test]
+    fn test_svg_root_id_class() {
+        let qr = QrCode::encode_text("Test", QrCodeEcc::Low).unwrap();
+        let options = SvgOptions { id: Some("qr-1".to_string()), class: Some("qr code".to_string()), ..SvgOptions::default() };
+        assert!(to_svg_string_with(&qr, &options).starts_with(r#"<svg id="qr-1" class="qr code" xmlns="#));
+        assert!(to_svg_string(&qr, 4, 1).starts_with("<svg xmlns="));
+        
+        let options = SvgOptions { id: Some(r#"a"b"#.to_string()), class: Some("x<y & z".to_string()), ..SvgOptions::default() };
+        assert!(to_svg_string_with(&qr, &options).starts_with(r#"<svg id="a&quot;b" class="x&lt;y &amp; z" xmlns="#));
+    }
+    
+    #[test]
+    fn test_svg_gradient_ids() {
+        let qr = QrCode::encode_text("Test", QrCodeEcc::Low).unwrap();
+        let svg_for = |id: &str| {
+            let options = SvgOptions { id: Some(id.to_string()), ..SvgOptions::default() };
+            to_svg_string_gradient_with(&qr, &options, "#000000", "#4d3695", GradientDirection::Vertical)
+        };
+        let first = svg_for("first");
+        assert!(first.starts_with(r#"<svg id="first" xmlns="#));
+        assert!(first.contains(r#"<linearGradient id="first-gradient""#));
+        assert!(first.contains(r#"fill="url(#first-gradient)""#));
+        assert!(!first.contains("qr-gradient"));
+        assert!(svg_for("second").contains(r#"<linearGradient id="second-gradient""#));
+        assert_eq!(to_svg_string_gradient(&qr, 4, 1, "#000000", "#4d3695", GradientDirection::Vertical),
+            to_svg_string_gradient_with(&qr, &SvgOptions::default(), "#000000", "#4d3695", GradientDirection::Vertical));
+    }
+    
+    #[test]
+    fn test_svg_physical() {
+        let qr = QrCode::encode_text("Test", QrCodeEcc::Low).unwrap();
+        let svg = to_svg_physical(&qr, 4, 25.4, 290.0);
+        let width = svg.split(r#" width=""#).nth(1).unwrap().split('"').next().unwrap();
+        assert!(width.ends_with("mm"));
+        assert_eq!(width.trim_end_matches("mm").parse::<f32>().unwrap(), 25.4);
+        assert!(svg.contains(r#"height="25.4mm""#));
+        // One inch at 290 DPI, 29 modules of 10 dots each
+        assert!(svg.contains(r#"viewBox="0 0 290 290""#));
+        assert!(svg.contains("M40,40h10v10h-10z"));
+    }
+    
+    #[test]
+    fn test_to_json() {
+        let qr = QrCode::encode_text("JSON export", QrCodeEcc::Quartile).unwrap();
+        let json = to_json(&qr, 2);
+        // Read the scalar fields back
+        let field = |key: &str| {
+            let rest = json.split(&format!(r#""{}":"#, key)).nth(1).unwrap();
+            rest[..rest.find([',', '}']).unwrap()].to_string()
+        };
+        assert_eq!(field("size"), qr.size().to_string());
+        assert_eq!(field("border"), "2");
+        assert_eq!(field("version"), qr.version().value().to_string());
+        assert_eq!(field("ecc"), r#""Quartile""#);
+        assert_eq!(field("mask"), qr.mask().value().to_string());
+        
+        // Parse the nested module arrays and compare them with the matrix
+        let modules = json.split(r#""modules":[["#).nth(1).unwrap().split("]]").next().unwrap();
+        let grid: Vec<Vec<bool>> = modules
+            .split("],[")
+            .map(|row| row.split(',').map(|v| v.parse().unwrap()).collect())
+            .collect();
+        assert_eq!(grid, qr.to_matrix());
+    }
+    
+    #[test]
+    fn test_standalone_svg() {
+        let qr = QrCode::encode_text("Standalone", QrCodeEcc::Low).unwrap();
+        let fragment = to_svg_string_with(&qr, &SvgOptions::default());
+        assert!(fragment.starts_with("<svg"));
+        
+        let svg = to_svg_string_with(&qr, &SvgOptions { standalone: true, ..SvgOptions::default() });
+        assert!(svg.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        let mut lines = svg.lines().skip(1);
+        assert!(lines.next().unwrap().starts_with("<!DOCTYPE svg PUBLIC"));
+        // The rest is exactly the fragment
+        assert!(svg.ends_with(&fragment));
+    }
+    
+    #[test]
+    fn test_to_svg_bytes() {
+        let qr = QrCode::encode_text("Bytes", QrCodeEcc::Medium).unwrap();
+        assert_eq!(to_svg_bytes(&qr, 4, 3), to_svg_string(&qr, 4, 3).into_bytes());
+    }
+    
+    #[test]
+    fn test_size_for_render() {
+        let qr = QrCode::encode_text("Layout", QrCodeEcc::Low).unwrap();
+        for (border, module_size) in [(0, 1), (4, 1), (2, 10), (1, 3)] {
+            let w = qr.size_for_render(border, module_size);
+            assert!(to_svg_string(&qr, border, module_size).contains(&format!(r#"viewBox="0 0 {w} {w}""#)));
+        }
+    }
+    
+    #[test]
+    fn test_to_packed_bits() {
+        let qr = QrCode::encode_text("Framebuffer", QrCodeEcc::Medium).unwrap();
+        for border in [0, 2, 4] {
+            let (bits, width, height) = to_packed_bits(&qr, border);
+            assert_eq!(width, (qr.size() + border * 2) as usize);
+            assert_eq!(height, width);
+            assert_eq!(bits.len(), width.div_ceil(8) * height);
+            let bit = |x: usize, y: usize| bits[y * width.div_ceil(8) + x / 8] & (0x80 >> (x % 8)) != 0;
+            for y in 0..height {
+                for x in 0..width {
+                    assert_eq!(bit(x, y), qr.get_module(x as i32 - border, y as i32 - border));
+                }
+            }
+        }
+        // The top-left finder starts with a run of 7 dark modules, MSB first
+        let (bits, _, _) = to_packed_bits(&qr, 0);
+        assert_eq!(bits[0], 0b1111_1110);
+    }
+    
+    #[test]
+    fn test_rotation() {
+        let qr = QrCode::encode_text("Rotate", QrCodeEcc::Low).unwrap();
+        let upright = to_svg_string_with(&qr, &SvgOptions::default());
+        assert!(!upright.contains("rotate"));
+        
+        let svg = to_svg_string_with(&qr, &SvgOptions { rotation: Rotation::Deg90, ..SvgOptions::default() });
+        assert!(svg.contains(r#"<g transform="rotate(90 14.5 14.5)">"#));
+        assert!(svg.contains(r#"viewBox="0 0 29 29""#));
+        // Only the wrapping group is added
+        let unwrapped = svg.replace(r#"<g transform="rotate(90 14.5 14.5)">"#, "").replace("</g>\n</svg>", "\n</svg>");
+        assert_eq!(unwrapped, upright);
+    }
+    
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"hello"), "aGVsbG8=");
+        assert_eq!(base64_encode(b"hello!"), "aGVsbG8h");
+        assert_eq!(base64_encode(b"hi"), "aGk=");
+        assert_eq!(base64_decode("aGVsbG8="), Some(b"hello".to_vec()));
+        assert_eq!(base64_decode("aGVs\nbG8h"), Some(b"hello!".to_vec()));
+        assert_eq!(base64_decode("a*b"), None);
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(base64_decode(&base64_encode(&bytes)), Some(bytes));
+    }
+    
+    #[test]
+    fn test_svg_data_uri() {
+        let qr = QrCode::encode_text("Data URI", QrCodeEcc::Low).unwrap();
+        let uri = to_svg_data_uri(&qr, 4, 1);
+        let encoded = uri.strip_prefix("data:image/svg+xml;base64,").unwrap();
+        assert_eq!(base64_decode(encoded).unwrap(), to_svg_bytes(&qr, 4, 1));
+    }
+    
+    #[test]
+    fn test_background_radius() {
+        let qr = QrCode::encode_text("Rounded card", QrCodeEcc::Low).unwrap();
+        let square = to_svg_string_with(&qr, &SvgOptions { module_size: 10, ..SvgOptions::default() });
+        assert!(!square.contains("rx="));
+        
+        let rounded = to_svg_string_with(&qr, &SvgOptions { module_size: 10, background_radius: 24.0, ..SvgOptions::default() });
+        assert!(rounded.contains(r##"<rect width="290" height="290" rx="24" fill="#FFFFFF"/>"##));
+        assert_eq!(rounded.replace(r#" rx="24""#, ""), square);
+    }
+    
+    #[test]
+    fn test_svg_region() {
+        let qr = QrCode::encode_text("Tiled rendering", QrCodeEcc::Low).unwrap();
+        let svg = to_svg_region(&qr, 10, 5, 20, 9, 2);
+        assert!(svg.contains(r#"viewBox="20 10 20 8""#));
+        assert!(svg.contains(r##"<rect x="20" y="10" width="20" height="8" fill="#FFFFFF"/>"##));
+        
+        let path = svg.split(r#"<path d=""#).nth(1).unwrap().split('"').next().unwrap();
+        let mut drawn = Vec::new();
+        for cmd in path.split('M').skip(1) {
+            let (x, rest) = cmd.split_once(',').unwrap();
+            let y = rest.split('h').next().unwrap();
+            drawn.push((x.parse::<i32>().unwrap() / 2, y.parse::<i32>().unwrap() / 2));
+        }
+        let expected: Vec<(i32, i32)> = (5..9).flat_map(|y| (10..20).map(move |x| (x, y)))
+            .filter(|&(x, y)| qr.get_module(x, y))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(drawn, expected);
+        
+        // Past the edge of the code everything is light
+        let outside = to_svg_region(&qr, -4, -4, 0, 0, 1);
+        assert!(outside.contains(r#"viewBox="-4 -4 4 4""#));
+        assert!(outside.contains(r#"<path d="""#));
+    }
+    
+    #[test]
+    fn test_deterministic_output() {
+        let qr = QrCode::encode_text("https://example.com/reproducible", QrCodeEcc::Medium).unwrap();
+        let svg = to_svg_string(&qr, 4, 10);
+        assert_eq!(svg.as_bytes(), to_svg_string(&qr, 4, 10).as_bytes());
+        
+        let circles = to_svg_string_shaped(&qr, 4, 3, SimpleShape::Circle);
+        assert_eq!(circles, to_svg_string_shaped(&qr, 4, 3, SimpleShape::Circle));
+        assert!(circles.contains(r#"<circle cx="13.5" cy="13.5" r="1.35"/>"#));
+        
+        // Every attribute number uses '.' as the decimal point, with no digit grouping
+        for value in circles.split('"').skip(1).step_by(2).filter(|v| v.starts_with(|c: char| c.is_ascii_digit())) {
+            assert!(value.split(' ').all(|n| n.parse::<f32>().is_ok()), "{}", value);
+        }
+        assert!(svg.is_ascii() && circles.is_ascii());
+        
+        assert_eq!(fmt_coord(14.500001), "14.5");
+        assert_eq!(fmt_coord(-0.0001), "0");
+        assert_eq!(fmt_coord(290.0), "290");
+        assert_eq!(fmt_coord(0.1 + 0.2), "0.3");
+    }
 }
-
@@ -9,7 +9,7 @@
 //! QR Code segment encoding and mode selection.
 
 use std::convert::TryFrom;
-use crate::types::Version;
+use crate::types::{Version, QrError};
 
 /// Describes how a segment's data bits are interpreted.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -88,17 +88,15 @@ impl QrSegment {
 	/// Any text string can be converted to UTF-8 bytes and encoded as a byte mode segment.
 	pub fn make_bytes(data: &[u8]) -> Self {
 		let mut bb = BitBuffer(Vec::with_capacity(data.len().checked_mul(8).unwrap()));
-		for &b in data {
-			bb.append_bits(u32::from(b), 8);
-		}
+		bb.append_bytes(data);
 		QrSegment::new(QrSegmentMode::Byte, data.len(), bb.0)
 	}
 	
 	/// Returns a segment representing the given string of decimal digits encoded in numeric mode.
 	/// 
-	/// Panics if the string contains non-digit characters.
+	/// Panics if the string contains non-digit characters; see `try_make_numeric()`.
 	pub fn make_numeric(text: &str) -> Self {
-		assert!(text.bytes().all(|b| (b'0' ..= b'9').contains(&b)), "String contains non-numeric characters");
+		assert!(text.bytes().all(|b| b.is_ascii_digit()), "String contains non-numeric characters");
 		let mut bb = BitBuffer(Vec::with_capacity(
 			text.len().checked_mul(3).unwrap().checked_add(text.len().div_ceil(3)).unwrap()));
 		for chunk in text.as_bytes().chunks(3) {
@@ -109,12 +107,22 @@ impl QrSegment {
 		QrSegment::new(QrSegmentMode::Numeric, text.len(), bb.0)
 	}
 	
+	/// Returns a segment representing the given string of decimal digits encoded in numeric mode.
+	/// 
+	/// Returns `Err` naming the first non-digit character instead of panicking.
+	pub fn try_make_numeric(text: &str) -> Result<Self, QrError> {
+		match text.chars().find(|c| !c.is_ascii_digit()) {
+			Some(c) => Err(QrError::InvalidCharacter(QrSegmentMode::Numeric, c)),
+			None => Ok(QrSegment::make_numeric(text)),
+		}
+	}
+	
 	/// Returns a segment representing the given text string encoded in alphanumeric mode.
 	/// 
 	/// The characters allowed are: 0 to 9, A to Z (uppercase only), space,
 	/// dollar, percent, asterisk, plus, hyphen, period, slash, colon.
 	/// 
-	/// Panics if the string contains non-encodable characters.
+	/// Panics if the string contains non-encodable characters; see `try_make_alphanumeric()`.
 	pub fn make_alphanumeric(text: &str) -> Self {
 		let mut bb = BitBuffer(Vec::with_capacity(
 			text.len().checked_mul(5).unwrap().checked_add(text.len().div_ceil(2)).unwrap()));
@@ -126,6 +134,16 @@ impl QrSegment {
 		QrSegment::new(QrSegmentMode::Alphanumeric, text.len(), bb.0)
 	}
 	
+	/// Returns a segment representing the given text string encoded in alphanumeric mode.
+	/// 
+	/// Returns `Err` naming the first non-encodable character instead of panicking.
+	pub fn try_make_alphanumeric(text: &str) -> Result<Self, QrError> {
+		match text.chars().find(|&c| !ALPHANUMERIC_CHARSET.contains(c)) {
+			Some(c) => Err(QrError::InvalidCharacter(QrSegmentMode::Alphanumeric, c)),
+			None => Ok(QrSegment::make_alphanumeric(text)),
+		}
+	}
+	
 	/// Returns a list of zero or more segments to represent the given Unicode text string.
 	/// 
 	/// The result may use various segment modes and switch
@@ -135,17 +153,115 @@ impl QrSegment {
 			vec![]
 		} else {
 			vec![
-				if QrSegment::is_numeric(text) {
-					QrSegment::make_numeric(text)
-				} else if QrSegment::is_alphanumeric(text) {
-					QrSegment::make_alphanumeric(text)
-				} else {
-					QrSegment::make_bytes(text.as_bytes())
+				match QrSegment::classify(text) {
+					QrSegmentMode::Numeric      => QrSegment::make_numeric(text),
+					QrSegmentMode::Alphanumeric => QrSegment::make_alphanumeric(text),
+					_                           => QrSegment::make_bytes(text.as_bytes()),
 				}
 			]
 		}
 	}
 	
+	// Returns the most compact of numeric, alphanumeric and byte mode that can encode
+	// the whole string, in a single pass that stops at the first byte-only character.
+	fn classify(text: &str) -> QrSegmentMode {
+		let mut result = QrSegmentMode::Numeric;
+		for b in text.bytes() {
+			if b.is_ascii_digit() {
+				continue;
+			} else if ALPHANUMERIC_CHARSET.as_bytes().contains(&b) {
+				result = QrSegmentMode::Alphanumeric;
+			} else {
+				return QrSegmentMode::Byte;
+			}
+		}
+		result
+	}
+	
+	/// Returns a list of zero or more segments to represent the given Unicode text string,
+	/// switching between numeric, alphanumeric and byte mode wherever that shortens the
+	/// bit stream in a QR Code of the given version.
+	/// 
+	/// Unlike `make_segments()`, which uses a single mode for the whole text, this finds
+	/// the optimal split, e.g. a long run of digits inside a URL is encoded numerically.
+	/// The result depends on the version only through the widths of the character count
+	/// fields, which change at versions 10 and 27.
+	pub fn make_segments_optimally(text: &str, ver: Version) -> Vec<Self> {
+		if text.is_empty() {
+			return vec![];
+		}
+		let chars: Vec<char> = text.chars().collect();
+		let charmodes: Vec<QrSegmentMode> = QrSegment::compute_character_modes(&chars, ver);
+		let mut result = Vec::<Self>::new();
+		let mut start: usize = 0;
+		while start < chars.len() {
+			let mode: QrSegmentMode = charmodes[start];
+			let end: usize = (start .. chars.len()).find(|&i| charmodes[i] != mode).unwrap_or(chars.len());
+			let run: String = chars[start .. end].iter().collect();
+			result.push(match mode {
+				QrSegmentMode::Numeric      => QrSegment::make_numeric(&run),
+				QrSegmentMode::Alphanumeric => QrSegment::make_alphanumeric(&run),
+				_                           => QrSegment::make_bytes(run.as_bytes()),
+			});
+			start = end;
+		}
+		result
+	}
+	
+	// Returns the mode each character is encoded in by the shortest bit stream,
+	// found by dynamic programming over the modes. Costs are in sixths of a bit.
+	fn compute_character_modes(chars: &[char], ver: Version) -> Vec<QrSegmentMode> {
+		const MODES: [QrSegmentMode; 3] = [QrSegmentMode::Byte, QrSegmentMode::Alphanumeric, QrSegmentMode::Numeric];
+		let headcosts: Vec<usize> = MODES.iter()
+			.map(|mode| (4 + usize::from(mode.num_char_count_bits(ver))) * 6).collect();
+		
+		// charmodes[i][j] is the mode of chars[i] in the cheapest encoding of chars[0 ..= i]
+		// whose current segment is in MODES[j]
+		let mut charmodes: Vec<[Option<QrSegmentMode>; 3]> = Vec::with_capacity(chars.len());
+		let mut prevcosts: Vec<usize> = headcosts.clone();
+		for &c in chars {
+			let mut curcosts: Vec<usize> = vec![usize::MAX; MODES.len()];
+			let mut curmodes: [Option<QrSegmentMode>; 3] = [None; 3];
+			
+			// Extend the current segment in each mode that can encode the character
+			curcosts[0] = prevcosts[0] + c.len_utf8() * 8 * 6;
+			curmodes[0] = Some(MODES[0]);
+			if QrSegment::alphanumeric_value(c).is_some() {
+				curcosts[1] = prevcosts[1] + 33;  // 5.5 bits per character
+				curmodes[1] = Some(MODES[1]);
+			}
+			if c.is_ascii_digit() {
+				curcosts[2] = prevcosts[2] + 20;  // 3.33 bits per digit
+				curmodes[2] = Some(MODES[2]);
+			}
+			
+			// Or end the segment (rounding up to whole bits) and start a new one in another mode
+			for j in 0 .. MODES.len() {
+				for k in 0 .. MODES.len() {
+					if let Some(mode) = curmodes[k] {
+						let newcost: usize = curcosts[k].div_ceil(6) * 6 + headcosts[j];
+						if newcost < curcosts[j] {
+							curcosts[j] = newcost;
+							curmodes[j] = Some(mode);
+						}
+					}
+				}
+			}
+			charmodes.push(curmodes);
+			prevcosts = curcosts;
+		}
+		
+		// Trace back from the cheapest final state
+		let mut curindex: usize = (0 .. MODES.len()).min_by_key(|&j| prevcosts[j]).unwrap();
+		let mut result: Vec<QrSegmentMode> = vec![QrSegmentMode::Byte; chars.len()];
+		for i in (0 .. chars.len()).rev() {
+			let mode: QrSegmentMode = charmodes[i][curindex].unwrap();
+			result[i] = mode;
+			curindex = MODES.iter().position(|&m| m == mode).unwrap();
+		}
+		result
+	}
+	
 	/// Returns a segment representing an Extended Channel Interpretation
 	/// (ECI) designator with the given assignment value.
 	pub fn make_eci(assignval: u32) -> Self {
@@ -164,6 +280,29 @@ impl QrSegment {
 		QrSegment::new(QrSegmentMode::Eci, 0, bb.0)
 	}
 	
+	/// Returns a segment representing an Extended Channel Interpretation
+	/// (ECI) designator with the given assignment value.
+	/// 
+	/// Returns `Err` if the value is 1000000 or more, instead of panicking.
+	pub fn try_make_eci(assignval: u32) -> Result<Self, QrError> {
+		if assignval < 1_000_000 {
+			Ok(QrSegment::make_eci(assignval))
+		} else {
+			Err(QrError::EciOutOfRange(assignval))
+		}
+	}
+	
+	/// Returns a list of segments representing the given byte chunks, each preceded
+	/// by an ECI designator with the chunk's assignment value (e.g. 3 for ISO-8859-1,
+	/// 26 for UTF-8).
+	/// 
+	/// Panics if an assignment value is 1000000 or more.
+	pub fn make_eci_bytes(chunks: &[(u32, &[u8])]) -> Vec<Self> {
+		chunks.iter()
+			.flat_map(|&(assignval, data)| [QrSegment::make_eci(assignval), QrSegment::make_bytes(data)])
+			.collect()
+	}
+	
 	/// Creates a new QR Code segment with the given attributes and data.
 	/// 
 	/// The character count (numchars) must agree with the mode and
@@ -187,6 +326,15 @@ impl QrSegment {
 		&self.data
 	}
 	
+	/// Returns the number of bits this segment takes in a QR Code of the given version:
+	/// the 4-bit mode indicator, the character count field and the data.
+	/// 
+	/// This does not check that the character count fits its field, which only
+	/// matters for segments of thousands of characters at low versions.
+	pub fn encoded_len_bits(&self, version: Version) -> usize {
+		4 + usize::from(self.mode.num_char_count_bits(version)) + self.data.len()
+	}
+	
 	// Calculates and returns the number of bits needed to encode the given
 	// segments at the given version. The result is None if a segment has too many
 	// characters to fit its length field, or the total bits exceeds usize::MAX.
@@ -210,7 +358,7 @@ impl QrSegment {
 	/// 
 	/// A string is encodable iff each character is in the range 0 to 9.
 	pub fn is_numeric(text: &str) -> bool {
-		text.chars().all(|c| ('0' ..= '9').contains(&c))
+		text.chars().all(|c| c.is_ascii_digit())
 	}
 	
 	/// Tests whether the given string can be encoded as a segment in alphanumeric mode.
@@ -220,6 +368,17 @@ impl QrSegment {
 	pub fn is_alphanumeric(text: &str) -> bool {
 		text.chars().all(|c| ALPHANUMERIC_CHARSET.contains(c))
 	}
+	
+	/// Returns the 45 characters encodable in alphanumeric mode, ordered by their value.
+	pub fn alphanumeric_charset() -> &'static str {
+		ALPHANUMERIC_CHARSET
+	}
+	
+	/// Returns the value (0 to 44) that the given character encodes to in alphanumeric mode,
+	/// or `None` if it is not encodable (e.g. lowercase letters).
+	pub fn alphanumeric_value(c: char) -> Option<u8> {
+		ALPHANUMERIC_CHARSET.find(c).map(|i| i as u8)
+	}
 }
 
 // The set of all legal characters in alphanumeric mode,
@@ -239,5 +398,200 @@ impl BitBuffer {
 		assert!(len <= 31 && val >> len == 0, "Value out of range");
 		self.0.extend((0 .. i32::from(len)).rev().map(|i| crate::types::get_bit(val, i)));
 	}
+	
+	/// Appends all bits of the given bytes to this buffer, most significant bit first.
+	pub fn append_bytes(&mut self, data: &[u8]) {
+		for &b in data {
+			self.append_bits(u32::from(b), 8);
+		}
+	}
+	
+	/// Returns the number of bits in this buffer.
+	pub fn len(&self) -> usize {
+		self.0.len()
+	}
+	
+	/// Returns whether this buffer holds no bits.
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+	
+	/// Packs the bits into bytes in big endian, as the encoder does for data codewords.
+	/// 
+	/// If the length is not a multiple of 8, the last byte is padded with 0 bits.
+	pub fn into_bytes(self) -> Vec<u8> {
+		let mut result = vec![0u8; self.0.len().div_ceil(8)];
+		for (i, &bit) in self.0.iter().enumerate() {
+			result[i >> 3] |= u8::from(bit) << (7 - (i & 7));
+		}
+		result
+	}
+	
+	/// Appends the given number of low-order bits of the given value to this buffer.
+	/// 
+	/// Returns `Err` and leaves the buffer unchanged unless len ≤ 31 and val < 2<sup>len</sup>.
+	pub fn try_append_bits(&mut self, val: u32, len: u8) -> Result<(), QrError> {
+		if len <= 31 && val >> len == 0 {
+			self.append_bits(val, len);
+			Ok(())
+		} else {
+			Err(QrError::BitsOutOfRange(val, len))
+		}
+	}
 }
 
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn test_try_factories() {
+		assert!(QrSegment::try_make_numeric("0123").unwrap() == QrSegment::make_numeric("0123"));
+		assert!(matches!(QrSegment::try_make_numeric("12a4"),
+			Err(QrError::InvalidCharacter(QrSegmentMode::Numeric, 'a'))));
+		assert!(QrSegment::try_make_alphanumeric("AB-12").unwrap() == QrSegment::make_alphanumeric("AB-12"));
+		assert!(matches!(QrSegment::try_make_alphanumeric("ABc"),
+			Err(QrError::InvalidCharacter(QrSegmentMode::Alphanumeric, 'c'))));
+		assert!(QrSegment::try_make_eci(999_999).is_ok());
+		assert!(matches!(QrSegment::try_make_eci(1_000_000), Err(QrError::EciOutOfRange(1_000_000))));
+	}
+	
+	#[test]
+	fn test_try_append_bits() {
+		let mut bb = BitBuffer(Vec::new());
+		bb.try_append_bits(0b101, 3).unwrap();
+		assert!(matches!(bb.try_append_bits(8, 3), Err(QrError::BitsOutOfRange(8, 3))));
+		assert!(matches!(bb.try_append_bits(0, 32), Err(QrError::BitsOutOfRange(0, 32))));
+		assert_eq!(bb.0, vec![true, false, true]);
+	}
+	
+	#[test]
+	fn test_bit_buffer_bytes() {
+		let mut bb = BitBuffer(Vec::new());
+		assert!(bb.is_empty());
+		bb.append_bits(0xA5, 8);
+		assert_eq!(bb.len(), 8);
+		assert_eq!(bb.into_bytes(), vec![0xA5]);
+		
+		let mut bb = BitBuffer(Vec::new());
+		bb.append_bytes(&[0x12, 0x34]);
+		bb.append_bits(0b1, 1);
+		assert_eq!(bb.len(), 17);
+		assert_eq!(bb.into_bytes(), vec![0x12, 0x34, 0x80]);
+	}
+	
+	#[test]
+	fn test_make_eci_bytes() {
+		let segs: Vec<QrSegment> = QrSegment::make_eci_bytes(&[(3, b"Caf\xE9"), (26, "Café".as_bytes())]);
+		let modes: Vec<QrSegmentMode> = segs.iter().map(|seg| seg.mode()).collect();
+		assert_eq!(modes, vec![QrSegmentMode::Eci, QrSegmentMode::Byte, QrSegmentMode::Eci, QrSegmentMode::Byte]);
+		assert!(segs[0] == QrSegment::make_eci(3));
+		assert!(segs[2] == QrSegment::make_eci(26));
+		assert_eq!(segs[1].num_chars(), 4);
+		assert_eq!(segs[3].num_chars(), 5);
+	}
+	
+	// Decodes numeric mode data bits back into digits: groups of 10 bits per 3 digits,
+	// then a 4-bit (1 digit) or 7-bit (2 digit) tail.
+	fn decode_numeric(bits: &[bool], numchars: usize) -> String {
+		let read = |start: usize, len: usize| bits[start .. start + len].iter().fold(0u32, |acc, &b| acc << 1 | u32::from(b));
+		let mut result = String::new();
+		let mut pos: usize = 0;
+		while result.len() < numchars {
+			let digits: usize = (numchars - result.len()).min(3);
+			let len: usize = digits * 3 + 1;
+			result.push_str(&format!("{:0width$}", read(pos, len), width = digits));
+			pos += len;
+		}
+		assert_eq!(pos, bits.len());
+		result
+	}
+	
+	#[test]
+	fn test_numeric_tails() {
+		for (text, bits) in [("7", 4), ("42", 7), ("123", 10), ("0012", 14), ("98765", 17), ("000", 10), ("05", 7)] {
+			let seg = QrSegment::make_numeric(text);
+			assert_eq!(seg.data().len(), bits, "bit count of {:?}", text);
+			assert_eq!(seg.num_chars(), text.len());
+			assert_eq!(decode_numeric(seg.data(), seg.num_chars()), text);
+		}
+	}
+	
+	#[test]
+	fn test_numeric_bit_length_formula() {
+		let digits: String = "31415926535897932384626433832795".to_string();
+		for len in 0 ..= digits.len() {
+			let tailbits: usize = [0, 4, 7][len % 3];
+			let seg = QrSegment::make_numeric(&digits[.. len]);
+			assert_eq!(seg.data().len(), len / 3 * 10 + tailbits);
+			assert_eq!(decode_numeric(seg.data(), len), &digits[.. len]);
+		}
+	}
+	
+	#[test]
+	fn test_alphanumeric_value() {
+		assert_eq!(QrSegment::alphanumeric_charset().len(), 45);
+		assert_eq!(QrSegment::alphanumeric_value('0'), Some(0));
+		assert_eq!(QrSegment::alphanumeric_value('A'), Some(10));
+		assert_eq!(QrSegment::alphanumeric_value(':'), Some(44));
+		assert_eq!(QrSegment::alphanumeric_value('a'), None);
+		assert_eq!(QrSegment::alphanumeric_value('é'), None);
+		for (i, c) in QrSegment::alphanumeric_charset().chars().enumerate() {
+			assert_eq!(QrSegment::alphanumeric_value(c), Some(i as u8));
+		}
+	}
+	
+	#[test]
+	fn test_make_segments_optimally() {
+		let ver = Version::new(1);
+		assert!(QrSegment::make_segments_optimally("", ver).is_empty());
+		
+		// A long digit run inside lowercase text gets its own numeric segment
+		let segs = QrSegment::make_segments_optimally("order 12345678901234567890 shipped", ver);
+		let modes: Vec<QrSegmentMode> = segs.iter().map(|seg| seg.mode()).collect();
+		assert_eq!(modes, [QrSegmentMode::Byte, QrSegmentMode::Numeric, QrSegmentMode::Byte]);
+		assert_eq!(segs[1].num_chars(), 20);
+		
+		// Short runs are not worth a segment header of their own
+		let segs = QrSegment::make_segments_optimally("abc1def", ver);
+		assert_eq!(segs.len(), 1);
+		assert_eq!(segs[0].mode(), QrSegmentMode::Byte);
+		
+		// Never longer than the single-mode split, and control characters fall back to bytes
+		for text in ["HELLO WORLD 123", "https://example.com/ITEM/0012345678", "tab\there\n", "Größe 42"] {
+			let optimal = QrSegment::make_segments_optimally(text, ver);
+			let simple = QrSegment::make_segments(text);
+			assert!(QrSegment::get_total_bits(&optimal, ver).unwrap() <= QrSegment::get_total_bits(&simple, ver).unwrap());
+		}
+		let segs = QrSegment::make_segments_optimally("tab\there", ver);
+		assert_eq!(segs.len(), 1);
+		assert_eq!(segs[0].mode(), QrSegmentMode::Byte);
+	}
+	
+	#[test]
+	fn test_classify_matches_predicates() {
+		for text in ["0", "0123456789", "HELLO WORLD", "A1", "1A", "$%*+-./:", "hello", "HELLO!", "12 34", "Größe", "\t"] {
+			let expected = if QrSegment::is_numeric(text) {
+				QrSegmentMode::Numeric
+			} else if QrSegment::is_alphanumeric(text) {
+				QrSegmentMode::Alphanumeric
+			} else {
+				QrSegmentMode::Byte
+			};
+			assert_eq!(QrSegment::classify(text), expected, "{:?}", text);
+			assert_eq!(QrSegment::make_segments(text)[0].mode(), expected);
+		}
+	}
+	
+	#[test]
+	fn test_encoded_len_bits() {
+		let seg = QrSegment::make_bytes(b"0123456789");
+		assert_eq!(seg.encoded_len_bits(Version::new(1)), 4 + 8 + 80);
+		assert_eq!(seg.encoded_len_bits(Version::new(10)), 4 + 16 + 80);
+		let segs = [seg, QrSegment::make_numeric("12345"), QrSegment::make_eci(26)];
+		for ver in [1, 9, 10, 26, 27, 40] {
+			let ver = Version::new(ver);
+			assert_eq!(Some(segs.iter().map(|seg| seg.encoded_len_bits(ver)).sum()), QrSegment::get_total_bits(&segs, ver));
+		}
+	}
+}
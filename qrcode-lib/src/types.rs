@@ -8,6 +8,8 @@
 
 //! Basic types used throughout the QR code library.
 
+use crate::segment::QrSegmentMode;
+
 /// The error correction level in a QR Code symbol.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum QrCodeEcc {
@@ -22,6 +24,18 @@ pub enum QrCodeEcc {
 }
 
 impl QrCodeEcc {
+	/// Returns the approximate fraction of codewords this level can recover,
+	/// e.g. 0.07 for `Low` and 0.30 for `High`.
+	pub fn recovery_fraction(self) -> f32 {
+		use QrCodeEcc::*;
+		match self {
+			Low      => 0.07,
+			Medium   => 0.15,
+			Quartile => 0.25,
+			High     => 0.30,
+		}
+	}
+	
 	// Returns an unsigned 2-bit integer (in the range 0 to 3).
 	pub(crate) fn ordinal(self) -> usize {
 		use QrCodeEcc::*;
@@ -45,6 +59,46 @@ impl QrCodeEcc {
 	}
 }
 
+impl std::fmt::Display for QrCodeEcc {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		use QrCodeEcc::*;
+		f.write_str(match *self {
+			Low      => "Low",
+			Medium   => "Medium",
+			Quartile => "Quartile",
+			High     => "High",
+		})
+	}
+}
+
+impl std::str::FromStr for QrCodeEcc {
+	type Err = ParseEccError;
+	
+	/// Parses a single-letter (`"L"`, `"M"`, `"Q"`, `"H"`) or full level name, ignoring case.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		use QrCodeEcc::*;
+		match s.to_ascii_lowercase().as_str() {
+			"l" | "low"      => Ok(Low),
+			"m" | "medium"   => Ok(Medium),
+			"q" | "quartile" => Ok(Quartile),
+			"h" | "high"     => Ok(High),
+			_ => Err(ParseEccError(s.to_string())),
+		}
+	}
+}
+
+/// The error type when a string does not name an error correction level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEccError(pub String);
+
+impl std::error::Error for ParseEccError {}
+
+impl std::fmt::Display for ParseEccError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "Unknown error correction level: {:?}", self.0)
+	}
+}
+
 /// A number between 1 and 40 (inclusive).
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub struct Version(u8);
@@ -58,16 +112,55 @@ impl Version {
 	
 	/// Creates a version object from the given number.
 	/// 
-	/// Panics if the number is outside the range [1, 40].
+	/// Panics if the number is outside the range [1, 40]; see `try_new()`.
 	pub const fn new(ver: u8) -> Self {
 		assert!(Version::MIN.value() <= ver && ver <= Version::MAX.value(), "Version number out of range");
 		Self(ver)
 	}
 	
+	/// Creates a version object from the given number.
+	/// 
+	/// Returns `Err` if the number is outside the range [1, 40].
+	pub const fn try_new(ver: u8) -> Result<Self, QrError> {
+		if Version::MIN.value() <= ver && ver <= Version::MAX.value() {
+			Ok(Self(ver))
+		} else {
+			Err(QrError::VersionOutOfRange(ver))
+		}
+	}
+	
+	/// Returns an iterator over all versions, from 1 to 40.
+	pub fn all() -> impl Iterator<Item = Version> {
+		(Version::MIN.value() ..= Version::MAX.value()).map(Version)
+	}
+	
 	/// Returns the value, which is in the range [1, 40].
 	pub const fn value(self) -> u8 {
 		self.0
 	}
+	
+	/// Returns the side length of a symbol at this version, in modules (21 to 177).
+	pub const fn module_count(self) -> u32 {
+		self.0 as u32 * 4 + 17
+	}
+	
+	/// Returns the number of error correction blocks at this version and the given ECC level.
+	pub fn ecc_block_count(self, ecl: QrCodeEcc) -> usize {
+		crate::qrcode::QrCode::get_block_info(self, ecl).num_blocks
+	}
+	
+	/// Returns the number of 8-bit data codewords (excluding error correction codewords)
+	/// at this version and the given ECC level.
+	pub fn data_codewords(self, ecl: QrCodeEcc) -> usize {
+		crate::qrcode::QrCode::get_num_data_codewords(self, ecl)
+	}
+	
+	/// Returns the maximum number of bytes that fit in a single byte mode segment
+	/// at this version and the given ECC level.
+	pub fn byte_capacity(self, ecl: QrCodeEcc) -> usize {
+		let headerbits = 4 + usize::from(QrSegmentMode::Byte.num_char_count_bits(self));
+		(self.data_codewords(ecl) * 8 - headerbits) / 8
+	}
 }
 
 /// A number between 0 and 7 (inclusive).
@@ -77,12 +170,23 @@ pub struct Mask(u8);
 impl Mask {
 	/// Creates a mask object from the given number.
 	/// 
-	/// Panics if the number is outside the range [0, 7].
+	/// Panics if the number is outside the range [0, 7]; see `try_new()`.
 	pub const fn new(mask: u8) -> Self {
 		assert!(mask <= 7, "Mask value out of range");
 		Self(mask)
 	}
 	
+	/// Creates a mask object from the given number.
+	/// 
+	/// Returns `Err` if the number is outside the range [0, 7].
+	pub const fn try_new(mask: u8) -> Result<Self, QrError> {
+		if mask <= 7 {
+			Ok(Self(mask))
+		} else {
+			Err(QrError::MaskOutOfRange(mask))
+		}
+	}
+	
 	/// Returns the value, which is in the range [0, 7].
 	pub const fn value(self) -> u8 {
 		self.0
@@ -107,6 +211,8 @@ pub enum DataTooLong {
 	SegmentTooLong,
 	/// Data length exceeds capacity (data length, max capacity)
 	DataOverCapacity(usize, usize),
+	/// The minimum version is greater than the maximum version (minversion, maxversion)
+	InvalidVersionRange(Version, Version),
 }
 
 impl std::error::Error for DataTooLong {}
@@ -117,6 +223,113 @@ impl std::fmt::Display for DataTooLong {
 			Self::SegmentTooLong => write!(f, "Segment too long"),
 			Self::DataOverCapacity(datalen, maxcapacity) =>
 				write!(f, "Data length = {} bits, Max capacity = {} bits", datalen, maxcapacity),
+			Self::InvalidVersionRange(minversion, maxversion) =>
+				write!(f, "Min version {} is greater than max version {}", minversion.value(), maxversion.value()),
+		}
+	}
+}
+
+impl DataTooLong {
+	/// Tells whether the data cannot fit in a single QR Code at any setting, i.e. it is
+	/// longer than the capacity of version 40 at `QrCodeEcc::Low`. The data must be shortened
+	/// or split, as lowering the ECC level or raising the version cannot help.
+	/// 
+	/// For `SegmentTooLong` this returns `true`: at version 40 a segment only overflows its
+	/// length field when it is far beyond the capacity. (With a lower maximum version
+	/// passed to `encode_segments_advanced()`, raising it may still help.) For
+	/// `InvalidVersionRange` this returns `false`, as the error is in the arguments.
+	pub fn is_hopeless(&self) -> bool {
+		match *self {
+			Self::SegmentTooLong => true,
+			Self::InvalidVersionRange(..) => false,
+			Self::DataOverCapacity(datalen, _) =>
+				datalen > crate::qrcode::CAPACITY_BITS[QrCodeEcc::Low.ordinal()][usize::from(Version::MAX.value())],
+		}
+	}
+}
+
+/// The error type of the non-panicking `try_` functions.
+/// 
+/// Each variant corresponds to an argument that the panicking counterpart
+/// rejects, so untrusted input can be handled with `?` instead of `catch_unwind`.
+/// Encoding failures convert into this type via `From<DataTooLong>`.
+#[derive(Debug, Clone)]
+pub enum QrError {
+	/// The version number is outside the range [1, 40] (given number)
+	VersionOutOfRange(u8),
+	/// The mask number is outside the range [0, 7] (given number)
+	MaskOutOfRange(u8),
+	/// A character cannot be encoded in the segment mode (mode, offending character)
+	InvalidCharacter(QrSegmentMode, char),
+	/// The ECI assignment value is 1000000 or more (given value)
+	EciOutOfRange(u32),
+	/// The value does not fit in the bit length, or the length exceeds 31 (value, length)
+	BitsOutOfRange(u32, u8),
+	/// The data does not fit in any allowed version
+	DataTooLong(DataTooLong),
+	/// The module coordinates are out of bounds or of a function module (x, y)
+	InvalidModule(i32, i32),
+	/// The text starts or ends with whitespace (the whitespace character)
+	SurroundingWhitespace(char),
+	/// The text contains an invisible control character (the character)
+	NonPrintableCharacter(char),
+}
+
+impl std::error::Error for QrError {}
+
+impl std::fmt::Display for QrError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::VersionOutOfRange(ver) => write!(f, "Version number out of range: {}", ver),
+			Self::MaskOutOfRange(mask) => write!(f, "Mask value out of range: {}", mask),
+			Self::InvalidCharacter(mode, c) => write!(f, "Character {:?} cannot be encoded in {:?} mode", c, mode),
+			Self::EciOutOfRange(val) => write!(f, "ECI assignment value out of range: {}", val),
+			Self::BitsOutOfRange(val, len) => write!(f, "Value {} out of range for {} bits", val, len),
+			Self::DataTooLong(e) => write!(f, "{}", e),
+			Self::InvalidModule(x, y) => write!(f, "Module ({}, {}) is out of bounds or a function module", x, y),
+			Self::SurroundingWhitespace(c) => write!(f, "Text starts or ends with whitespace {:?}", c),
+			Self::NonPrintableCharacter(c) => write!(f, "Text contains non-printable character {:?}", c),
+		}
+	}
+}
+
+impl From<DataTooLong> for QrError {
+	fn from(e: DataTooLong) -> Self {
+		Self::DataTooLong(e)
+	}
+}
+
+/// The error type when a module matrix fails structural validation.
+/// 
+/// Returned by `QrCode::validate_structure()`, which checks the fixed patterns
+/// of a symbol without decoding its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructureError {
+	/// The matrix is not square with the side length of the version (expected side length)
+	WrongSize(usize),
+	/// A finder pattern or its separator is malformed (0 = top left, 1 = top right, 2 = bottom left)
+	FinderPattern(usize),
+	/// A timing pattern does not alternate between dark and light
+	TimingPattern,
+	/// The always-dark module next to the bottom left finder is light
+	DarkModule,
+	/// Neither copy of the format information is a correctable BCH codeword
+	FormatInfo,
+	/// Neither copy of the version information matches the version
+	VersionInfo,
+}
+
+impl std::error::Error for StructureError {}
+
+impl std::fmt::Display for StructureError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match *self {
+			Self::WrongSize(size) => write!(f, "Matrix is not {0}x{0} modules", size),
+			Self::FinderPattern(i) => write!(f, "Finder pattern {} is malformed", i),
+			Self::TimingPattern => write!(f, "Timing pattern does not alternate"),
+			Self::DarkModule => write!(f, "Dark module is missing"),
+			Self::FormatInfo => write!(f, "Format information is not a valid codeword"),
+			Self::VersionInfo => write!(f, "Version information does not match the version"),
 		}
 	}
 }
@@ -126,3 +339,63 @@ pub(crate) fn get_bit(x: u32, i: i32) -> bool {
 	(x >> i) & 1 != 0
 }
 
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn test_recovery_fraction() {
+		assert!((QrCodeEcc::High.recovery_fraction() - 0.30).abs() < 1e-6);
+		assert!((QrCodeEcc::Low.recovery_fraction() - 0.07).abs() < 1e-6);
+		assert!(QrCodeEcc::Medium.recovery_fraction() < QrCodeEcc::Quartile.recovery_fraction());
+	}
+	
+	#[test]
+	fn test_ecc_from_str() {
+		for s in ["q", "Quartile", "QUARTILE"] {
+			assert_eq!(s.parse::<QrCodeEcc>().unwrap(), QrCodeEcc::Quartile);
+		}
+		assert_eq!("L".parse::<QrCodeEcc>().unwrap(), QrCodeEcc::Low);
+		assert_eq!("high".parse::<QrCodeEcc>().unwrap(), QrCodeEcc::High);
+		assert!("x".parse::<QrCodeEcc>().is_err());
+		assert!("lowest".parse::<QrCodeEcc>().is_err());
+	}
+	
+	#[test]
+	fn test_ecc_display_round_trip() {
+		for ecl in [QrCodeEcc::Low, QrCodeEcc::Medium, QrCodeEcc::Quartile, QrCodeEcc::High] {
+			assert_eq!(ecl.to_string().parse::<QrCodeEcc>().unwrap(), ecl);
+		}
+	}
+	
+	#[test]
+	fn test_try_new() {
+		assert_eq!(Version::try_new(40).unwrap(), Version::MAX);
+		assert!(matches!(Version::try_new(0), Err(QrError::VersionOutOfRange(0))));
+		assert!(matches!(Version::try_new(41), Err(QrError::VersionOutOfRange(41))));
+		assert_eq!(Mask::try_new(7).unwrap().value(), 7);
+		assert!(matches!(Mask::try_new(8), Err(QrError::MaskOutOfRange(8))));
+	}
+	
+	#[test]
+	fn test_version_helpers() {
+		assert_eq!(Version::new(1).module_count(), 21);
+		assert_eq!(Version::new(40).module_count(), 177);
+		assert_eq!(Version::new(1).data_codewords(QrCodeEcc::Low), 19);
+		assert_eq!(Version::new(5).ecc_block_count(QrCodeEcc::Quartile), 4);
+		assert_eq!(Version::new(1).byte_capacity(QrCodeEcc::Low), 17);
+		assert_eq!(Version::MAX.byte_capacity(QrCodeEcc::Low), 2953);
+		assert_eq!(Version::MAX.byte_capacity(QrCodeEcc::High), 1273);
+	}
+	
+	#[test]
+	fn test_data_too_long_is_hopeless() {
+		use crate::QrCode;
+		let Err(err) = QrCode::encode_binary(&[0u8; 3000], QrCodeEcc::Low) else { panic!() };
+		assert!(err.is_hopeless());
+		// Fits at version 40 with Low, but not with High
+		let Err(err) = QrCode::encode_binary(&[0u8; 2000], QrCodeEcc::High) else { panic!() };
+		assert!(!err.is_hopeless());
+		assert!(QrCode::encode_binary(&[0u8; 2000], QrCodeEcc::Low).is_ok());
+	}
+}